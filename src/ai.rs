@@ -1,57 +1,267 @@
-use crate::game::{Game, PlayerAction, Action};
+use crate::game::{Game, Phase, PlayerAction, Action, is_legal_bet, legal_raises, minimum_raise};
 use macroquad::rand::gen_range;
 
-pub fn ai_decide_action(game: &Game) -> PlayerAction {
+/// How carefully an AI seat plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiDifficulty {
+    Easy,   // Bets at random and calls on a fixed threshold
+    Normal, // Bets on its own dice and calls bets above the expected count
+    Hard,   // Weighs the odds of every raise against calling
+}
+
+impl AiDifficulty {
+    pub fn name(self) -> &'static str {
+        match self {
+            AiDifficulty::Easy => "easy",
+            AiDifficulty::Normal => "normal",
+            AiDifficulty::Hard => "hard",
+        }
+    }
+}
+
+pub fn ai_decide_action(game: &Game, difficulty: AiDifficulty) -> PlayerAction {
+    match difficulty {
+        AiDifficulty::Easy => easy_action(game),
+        AiDifficulty::Normal => normal_action(game),
+        AiDifficulty::Hard => hard_action(game),
+    }
+}
+
+fn call() -> PlayerAction {
+    PlayerAction {
+        action: Action::Call,
+        bet: None,
+    }
+}
+
+fn bet((dice_count, face_value): (u8, u8)) -> PlayerAction {
+    PlayerAction {
+        action: Action::Bet,
+        bet: Some((dice_count, face_value)),
+    }
+}
+
+/// Calls when nothing higher can be bet
+fn bet_or_call(bet_choice: Option<(u8, u8)>) -> PlayerAction {
+    match bet_choice {
+        Some(choice) => bet(choice),
+        None => call(),
+    }
+}
+
+fn easy_action(game: &Game) -> PlayerAction {
     // If there is a bet, decide whether to call or bet
-    if !game.bets.is_empty() {
-        // Calculate total dice remaining in the game
-        let total_dice_remaining: u8 = game.current_player_dice_count.iter().sum();
-        
-        // Get the last bet
-        if let Some((_, bet_dice_count, _)) = game.bets.last() {
-            // Only call if the bet exceeds 1/6 of total dice remaining
-            if *bet_dice_count as f32 > (total_dice_remaining as f32 / 6.0) {
-                PlayerAction {
-                    action: Action::Call,
-                    bet: None,
-                }
-            } else {
-                // Make a higher bet instead of calling
-                let current_bet = game.bets.last().unwrap();
-                let (_, current_dice_count, current_face_value) = current_bet;
-                
-                // Try to increase the dice count or face value
-                let new_dice_count = if gen_range(0, 2) == 0 {
-                    *current_dice_count + 1
-                } else {
-                    *current_dice_count
-                };
-                
-                let new_face_value = if new_dice_count == *current_dice_count {
-                    (*current_face_value + 1).min(6)
-                } else {
-                    gen_range(1, 7)
-                };
-                
-                PlayerAction {
-                    action: Action::Bet,
-                    bet: Some((new_dice_count, new_face_value)),
-                }
-            }
+    if let Some(&(_, current_dice_count, current_face_value)) = game.bets.last() {
+        // Only call if the bet exceeds 1/6 of total dice remaining
+        if current_dice_count as f32 > (game.total_dice() as f32 / 6.0) {
+            return call();
+        }
+
+        // Try to increase the dice count or face value
+        let new_dice_count = if gen_range(0, 2) == 0 {
+            current_dice_count + 1
         } else {
-            // Fallback: call if no bet info available
-            PlayerAction {
-                action: Action::Call,
-                bet: None,
-            }
+            current_dice_count
+        };
+
+        let new_face_value = if new_dice_count == current_dice_count {
+            (current_face_value + 1).min(6)
+        } else {
+            gen_range(1, 7)
+        };
+
+        // A face of 6 can't be raised in place, so fall back to the smallest raise
+        let choice = (new_dice_count, new_face_value);
+        if is_legal_bet(game, choice) {
+            bet(choice)
+        } else {
+            bet_or_call(minimum_raise(game))
         }
     } else {
         // Otherwise, make a random valid bet
         let dice_count = gen_range(1, game.current_player_dice_count[game.current_player as usize] + 1);
         let face_value = gen_range(1, 7);
-        PlayerAction {
-            action: Action::Bet,
-            bet: Some((dice_count, face_value)),
+        bet((dice_count, face_value))
+    }
+}
+
+fn normal_action(game: &Game) -> PlayerAction {
+    if let Some(&(_, current_dice_count, current_face_value)) = game.bets.last() {
+        if current_dice_count as f32 > expected_count(game, game.current_player, current_face_value) {
+            return call();
+        }
+    }
+
+    // Raise on the face this player holds most of, otherwise make the smallest raise
+    let raises = legal_raises(game);
+    let best_face = (1..=6).max_by_key(|&face| own_count(game, game.current_player, face)).unwrap_or(1);
+    let choice = raises.iter().copied().find(|&(_, face)| face == best_face).or_else(|| raises.first().copied());
+    bet_or_call(choice)
+}
+
+fn hard_action(game: &Game) -> PlayerAction {
+    let call_odds = match game.bets.last() {
+        // Calling wins when the bet does not hold
+        Some(&(_, count, face)) => 1.0 - bet_probability(game, game.current_player, (count, face)),
+        None => 0.0,
+    };
+
+    // The safest raise, preferring smaller counts when the odds are level
+    let best_raise = legal_raises(game)
+        .into_iter()
+        .map(|raise| (raise, bet_probability(game, game.current_player, raise)))
+        .fold(None, |best: Option<((u8, u8), f32)>, (raise, odds)| match best {
+            Some((_, best_odds)) if best_odds >= odds => best,
+            _ => Some((raise, odds)),
+        });
+
+    match best_raise {
+        Some((raise, odds)) if odds >= call_odds => bet(raise),
+        _ => call(),
+    }
+}
+
+/// Dice showing a face in a player's own hand
+fn own_count(game: &Game, player: u8, face_value: u8) -> u8 {
+    let player = player as usize;
+    let dice_count = game.current_player_dice_count[player] as usize;
+    game.player_dice[player][..dice_count]
+        .iter()
+        .filter(|&&die| die == face_value || (game.rules.ones_wild && die == 1 && face_value != 1))
+        .count() as u8
+}
+
+/// Chance that a single hidden die counts towards a face
+fn face_probability(game: &Game, face_value: u8) -> f32 {
+    if game.rules.ones_wild && face_value != 1 {
+        2.0 / 6.0
+    } else {
+        1.0 / 6.0
+    }
+}
+
+/// How many dice a player can expect to show a face, counting their own hand
+fn expected_count(game: &Game, player: u8, face_value: u8) -> f32 {
+    let hidden_dice = game.total_dice() - game.current_player_dice_count[player as usize];
+    own_count(game, player, face_value) as f32 + hidden_dice as f32 * face_probability(game, face_value)
+}
+
+/// Chance that a bet holds, given what a player can see
+fn bet_probability(game: &Game, player: u8, (dice_count, face_value): (u8, u8)) -> f32 {
+    let hidden_dice = game.total_dice() - game.current_player_dice_count[player as usize];
+    let needed = dice_count.saturating_sub(own_count(game, player, face_value));
+    probability_at_least(hidden_dice, face_probability(game, face_value), needed)
+}
+
+/// What the hint overlay tells a player about the round
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub bid_probability: Option<f32>, // Chance the last bid holds
+    pub expected_counts: [f32; 6],    // Dice expected to count towards each face, from 1 to 6
+    pub suggestion: Option<PlayerAction>, // What the hard AI would do, on the player's own turn
+}
+
+/// Works out a hint from the player's own hand only. The other hands are blanked out
+/// before anything is counted.
+pub fn hint(game: &Game, player: u8) -> Hint {
+    let view = game.seen_by(player);
+    Hint {
+        bid_probability: view.bets.last().map(|&(_, count, face)| bet_probability(&view, player, (count, face))),
+        expected_counts: std::array::from_fn(|face| expected_count(&view, player, face as u8 + 1)),
+        suggestion: (view.current_player == player && view.phase == Phase::Bidding).then(|| hard_action(&view)),
+    }
+}
+
+/// Chance of at least `needed` successes in `trials` rolls that each succeed with `p`
+fn probability_at_least(trials: u8, p: f32, needed: u8) -> f32 {
+    if needed > trials {
+        return 0.0;
+    }
+    (needed..=trials)
+        .map(|successes| {
+            binomial(trials, successes) * p.powi(successes as i32) * (1.0 - p).powi((trials - successes) as i32)
+        })
+        .sum()
+}
+
+fn binomial(n: u8, k: u8) -> f32 {
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f32 / (i + 1) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{take_action, Rules};
+
+    fn create_test_game() -> Game {
+        let mut game = Game::with_players(2, Rules::default());
+        game.player_dice[0] = [6, 6, 6, 2, 3];
+        game.player_dice[1] = [1, 2, 3, 4, 5];
+        game
+    }
+
+    #[test]
+    fn test_probability_at_least() {
+        assert!((probability_at_least(3, 0.5, 0) - 1.0).abs() < 0.001);
+        assert!((probability_at_least(3, 0.5, 3) - 0.125).abs() < 0.001);
+        assert!((probability_at_least(3, 0.5, 2) - 0.5).abs() < 0.001);
+        assert_eq!(probability_at_least(2, 0.5, 3), 0.0);
+    }
+
+    #[test]
+    fn test_every_difficulty_makes_a_legal_move() {
+        for difficulty in [AiDifficulty::Easy, AiDifficulty::Normal, AiDifficulty::Hard] {
+            let mut game = create_test_game();
+            // Face 6 can't be raised in place, which used to trip up the easy AI
+            game = take_action(&game, &bet((1, 6))).unwrap();
+            for _ in 0..20 {
+                let action = ai_decide_action(&game, difficulty);
+                assert!(take_action(&game, &action).is_ok(), "{:?} made an illegal move", difficulty);
+            }
         }
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_hard_calls_impossible_bet() {
+        let mut game = create_test_game();
+        game = take_action(&game, &bet((10, 4))).unwrap();
+        game = take_action(&game, &bet((10, 5))).unwrap();
+        // Player 0 holds no 5s, so ten of them among ten dice can't hold
+        assert_eq!(ai_decide_action(&game, AiDifficulty::Hard).action, Action::Call);
+    }
+
+    #[test]
+    fn test_hard_bets_on_its_own_dice() {
+        let game = create_test_game();
+        let action = ai_decide_action(&game, AiDifficulty::Hard);
+        assert_eq!(action.action, Action::Bet);
+        // Any bet of up to three 6s is certain for player 0
+        let (count, face) = action.bet.unwrap();
+        assert!(face == 6 || count <= own_count(&game, 0, face));
+    }
+
+    #[test]
+    fn test_hint_only_uses_the_players_own_hand() {
+        let mut game = create_test_game();
+        game = take_action(&game, &bet((3, 6))).unwrap();
+        let hint_for_one = hint(&game, 1);
+
+        // Player 1 can't tell what player 0 holds
+        game.player_dice[0] = [1, 1, 1, 1, 1];
+        assert_eq!(hint(&game, 1), hint_for_one);
+
+        // Player 1 holds one 5 and expects a sixth of player 0's five dice to match
+        assert!((hint_for_one.expected_counts[4] - (1.0 + 5.0 / 6.0)).abs() < 0.001);
+        assert!(hint_for_one.bid_probability.unwrap() < 0.1);
+        assert_eq!(hint_for_one.suggestion, Some(call()));
+    }
+
+    #[test]
+    fn test_hint_only_suggests_on_the_players_turn() {
+        let game = create_test_game();
+        let waiting = hint(&game, 1);
+        assert_eq!(waiting.suggestion, None);
+        assert_eq!(waiting.bid_probability, None);
+        assert!(hint(&game, 0).suggestion.is_some());
+    }
+}
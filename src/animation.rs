@@ -0,0 +1,223 @@
+// Dice roll and reveal animations for Rusty Dice
+
+use crate::game::RoundResolution;
+
+/// How long each part of the roll and reveal sequence takes, in seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationConfig {
+    pub flip_duration: f32,      // Per player, hands flip over one at a time
+    pub highlight_duration: f32, // Dice matching the challenged face light up
+    pub removal_duration: f32,   // The loser's die leaves the table
+    pub roll_duration: f32,      // Dice tumble while the next round is rolled
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        Self {
+            flip_duration: 0.4,
+            highlight_duration: 1.5,
+            removal_duration: 0.8,
+            roll_duration: 0.8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnimationStage {
+    Flipping,
+    Highlighting,
+    RemovingDie(f32), // Progress from 0.0 to 1.0
+    Rolling,
+    Done,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    pub config: AnimationConfig,
+    pub reveal: Option<RoundResolution>,
+    elapsed: f32,
+}
+
+impl Animation {
+    /// Dice tumble before settling on a freshly rolled round
+    pub fn roll(config: AnimationConfig) -> Self {
+        Self {
+            config,
+            reveal: None,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Hands flip over, matches light up and the loser's die leaves, then the next round is rolled
+    pub fn reveal(config: AnimationConfig, reveal: RoundResolution) -> Self {
+        Self {
+            config,
+            reveal: Some(reveal),
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, delta_seconds: f32) {
+        self.elapsed = (self.elapsed + delta_seconds).min(self.total_duration());
+    }
+
+    /// Jumps straight to the end of the sequence
+    pub fn skip(&mut self) {
+        self.elapsed = self.total_duration();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.total_duration()
+    }
+
+    /// True while the previous round's hands are on the table
+    pub fn is_revealing(&self) -> bool {
+        self.reveal.is_some() && self.elapsed < self.reveal_duration()
+    }
+
+    pub fn stage(&self) -> AnimationStage {
+        let flips_end = self.flip_duration();
+        let highlight_end = flips_end + self.config.highlight_duration;
+        let reveal_end = self.reveal_duration();
+
+        if self.is_finished() {
+            AnimationStage::Done
+        } else if self.elapsed >= reveal_end {
+            AnimationStage::Rolling
+        } else if self.elapsed < flips_end {
+            AnimationStage::Flipping
+        } else if self.elapsed < highlight_end {
+            AnimationStage::Highlighting
+        } else {
+            let progress = (self.elapsed - highlight_end) / self.config.removal_duration;
+            AnimationStage::RemovingDie(progress)
+        }
+    }
+
+    /// Whether a player's hand has been flipped face-up yet. Hands flip in seat order,
+    /// skipping players who had no dice.
+    pub fn is_hand_flipped(&self, player: usize) -> bool {
+        let Some(reveal) = &self.reveal else {
+            return false;
+        };
+        if reveal.hands.dice_count[player] == 0 {
+            return false;
+        }
+
+        let flip_order = reveal.hands.dice_count[..player]
+            .iter()
+            .filter(|&&count| count > 0)
+            .count();
+        self.elapsed >= (flip_order + 1) as f32 * self.config.flip_duration
+    }
+
+    fn flip_duration(&self) -> f32 {
+        match &self.reveal {
+            Some(reveal) => {
+                let players_with_dice = reveal.hands.dice_count.iter().filter(|&&count| count > 0).count();
+                players_with_dice as f32 * self.config.flip_duration
+            }
+            None => 0.0,
+        }
+    }
+
+    fn reveal_duration(&self) -> f32 {
+        match &self.reveal {
+            Some(_) => self.flip_duration() + self.config.highlight_duration + self.config.removal_duration,
+            None => 0.0,
+        }
+    }
+
+    fn total_duration(&self) -> f32 {
+        self.reveal_duration() + self.config.roll_duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::RoundHands;
+
+    fn create_test_reveal() -> RoundResolution {
+        let mut dice_count = [0; 8];
+        dice_count[0] = 5;
+        dice_count[1] = 0; // Player 1 is already out
+        dice_count[2] = 3;
+        RoundResolution {
+            hands: RoundHands {
+                dice_count,
+                player_dice: [[1; 5]; 8],
+            },
+            bet: (0, 9, 1),
+            caller: 2,
+            actual_count: 8,
+            wild_count: 0,
+            losing_player: 2,
+        }
+    }
+
+    #[test]
+    fn test_roll_animation_only_tumbles() {
+        let mut animation = Animation::roll(AnimationConfig::default());
+        assert_eq!(animation.stage(), AnimationStage::Rolling);
+        assert!(!animation.is_revealing());
+
+        animation.update(0.8);
+        assert!(animation.is_finished());
+        assert_eq!(animation.stage(), AnimationStage::Done);
+    }
+
+    #[test]
+    fn test_reveal_stages_run_in_order() {
+        let mut animation = Animation::reveal(AnimationConfig::default(), create_test_reveal());
+        assert_eq!(animation.stage(), AnimationStage::Flipping);
+
+        // Two players with dice flip at 0.4s each
+        animation.update(0.9);
+        assert_eq!(animation.stage(), AnimationStage::Highlighting);
+
+        animation.update(1.8);
+        match animation.stage() {
+            AnimationStage::RemovingDie(progress) => assert!((progress - 0.5).abs() < 0.01),
+            stage => panic!("Expected the die to be leaving, got {:?}", stage),
+        }
+        assert!(animation.is_revealing());
+
+        animation.update(0.5);
+        assert_eq!(animation.stage(), AnimationStage::Rolling);
+        assert!(!animation.is_revealing());
+    }
+
+    #[test]
+    fn test_hands_flip_one_player_at_a_time() {
+        let mut animation = Animation::reveal(AnimationConfig::default(), create_test_reveal());
+        assert!(!animation.is_hand_flipped(0));
+
+        animation.update(0.4);
+        assert!(animation.is_hand_flipped(0));
+        assert!(!animation.is_hand_flipped(2));
+
+        // Player 1 had no dice, so player 2 is next to flip
+        animation.update(0.4);
+        assert!(!animation.is_hand_flipped(1));
+        assert!(animation.is_hand_flipped(2));
+    }
+
+    #[test]
+    fn test_skip_jumps_to_end() {
+        let mut animation = Animation::reveal(AnimationConfig::default(), create_test_reveal());
+        animation.skip();
+        assert!(animation.is_finished());
+        assert_eq!(animation.stage(), AnimationStage::Done);
+    }
+
+    #[test]
+    fn test_update_does_not_run_past_the_end() {
+        let mut animation = Animation::roll(AnimationConfig::default());
+        animation.update(100.0);
+        assert!(animation.is_finished());
+        animation.update(1.0);
+        assert!(animation.is_finished());
+    }
+}
@@ -0,0 +1,248 @@
+// Sound effects: which game events make a sound, how loud they play, and playing them
+
+use macroquad::audio::{load_sound_from_bytes, play_sound, PlaySoundParams, Sound};
+
+use crate::game::{Game, GameEvent, Phase};
+use crate::i18n::tr;
+
+// Seconds after a call that the dice are heard turning over and the loser's die is taken,
+// roughly in step with the reveal animation
+const REVEAL_CUE: f32 = 0.3;
+const RESULT_CUE: f32 = 1.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundEffect {
+    DiceShake,
+    Bid,
+    Call,
+    Reveal,
+    DieLost,
+    Elimination,
+    Victory,
+}
+
+impl SoundEffect {
+    pub const ALL: [SoundEffect; 7] = [
+        SoundEffect::DiceShake,
+        SoundEffect::Bid,
+        SoundEffect::Call,
+        SoundEffect::Reveal,
+        SoundEffect::DieLost,
+        SoundEffect::Elimination,
+        SoundEffect::Victory,
+    ];
+
+    /// The bundled WAV file for this effect
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            SoundEffect::DiceShake => include_bytes!("../assets/sounds/dice_shake.wav"),
+            SoundEffect::Bid => include_bytes!("../assets/sounds/bid.wav"),
+            SoundEffect::Call => include_bytes!("../assets/sounds/call.wav"),
+            SoundEffect::Reveal => include_bytes!("../assets/sounds/reveal.wav"),
+            SoundEffect::DieLost => include_bytes!("../assets/sounds/die_lost.wav"),
+            SoundEffect::Elimination => include_bytes!("../assets/sounds/elimination.wav"),
+            SoundEffect::Victory => include_bytes!("../assets/sounds/victory.wav"),
+        }
+    }
+
+    pub fn channel(self) -> Channel {
+        match self {
+            SoundEffect::DiceShake | SoundEffect::Reveal => Channel::Dice,
+            SoundEffect::Bid | SoundEffect::Call => Channel::Table,
+            SoundEffect::DieLost | SoundEffect::Elimination | SoundEffect::Victory => Channel::Results,
+        }
+    }
+}
+
+/// A group of effects sharing one volume slider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Dice,    // Shaking and revealing the dice
+    Table,   // Bids and calls
+    Results, // Dice lost, eliminations and the winner
+}
+
+impl Channel {
+    pub const ALL: [Channel; 3] = [Channel::Dice, Channel::Table, Channel::Results];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Channel::Dice => "dice",
+            Channel::Table => "table",
+            Channel::Results => "results",
+        }
+    }
+
+    pub fn label(self) -> String {
+        tr(&format!("channel.{}", self.name()))
+    }
+}
+
+/// Volumes from 0 to 1, saved with the other preferences
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mixer {
+    pub master: f32,
+    pub channels: [f32; 3], // In the order of `Channel::ALL`
+    pub muted: bool,
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self {
+            master: 0.8,
+            channels: [1.0; 3],
+            muted: false,
+        }
+    }
+}
+
+impl Mixer {
+    pub fn channel_volume(&self, channel: Channel) -> f32 {
+        self.channels[channel as usize]
+    }
+
+    pub fn set_channel_volume(&mut self, channel: Channel, volume: f32) {
+        self.channels[channel as usize] = volume.clamp(0.0, 1.0);
+    }
+
+    /// How loud an effect plays: nothing when muted, otherwise its channel scaled by the master
+    pub fn volume(&self, effect: SoundEffect) -> f32 {
+        if self.muted {
+            return 0.0;
+        }
+        self.master * self.channel_volume(effect.channel())
+    }
+}
+
+/// The sounds for what happened between two states of the table, each with the seconds to
+/// wait before playing it so a call, its reveal and its result are heard in order
+pub fn sound_cues(before: &Game, after: &Game) -> Vec<(f32, SoundEffect)> {
+    let mut cues = Vec::new();
+    if before.phase == Phase::Dealing && after.phase == Phase::Bidding {
+        cues.push((0.0, SoundEffect::DiceShake));
+    }
+
+    let new_events = after.events.get(before.events.len()..).unwrap_or_default();
+    for event in new_events {
+        match event {
+            GameEvent::Bet { .. } => cues.push((0.0, SoundEffect::Bid)),
+            GameEvent::Call(_) => cues.extend([
+                (0.0, SoundEffect::Call),
+                (REVEAL_CUE, SoundEffect::Reveal),
+                (RESULT_CUE, SoundEffect::DieLost),
+            ]),
+            GameEvent::TimedOut { .. } => cues.push((0.0, SoundEffect::DieLost)),
+            GameEvent::Eliminated(_) => cues.push((RESULT_CUE + 0.4, SoundEffect::Elimination)),
+            GameEvent::Won { .. } => cues.push((RESULT_CUE + 1.2, SoundEffect::Victory)),
+        }
+    }
+    cues
+}
+
+/// Every effect, decoded and ready to play
+pub struct SoundBank {
+    sounds: Vec<(SoundEffect, Sound)>,
+}
+
+impl SoundBank {
+    pub async fn load() -> Result<Self, String> {
+        let mut sounds = Vec::new();
+        for effect in SoundEffect::ALL {
+            let sound = load_sound_from_bytes(effect.bytes())
+                .await
+                .map_err(|e| format!("Could not load the {:?} sound: {}", effect, e))?;
+            sounds.push((effect, sound));
+        }
+        Ok(Self { sounds })
+    }
+
+    pub fn play(&self, effect: SoundEffect, mixer: &Mixer) {
+        let volume = mixer.volume(effect);
+        if volume <= 0.0 {
+            return;
+        }
+        if let Some((_, sound)) = self.sounds.iter().find(|(sound_effect, _)| *sound_effect == effect) {
+            play_sound(sound, PlaySoundParams { looped: false, volume });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::{advance_phase, take_action, Action, PlayerAction, Rules};
+
+    fn cue_effects(cues: &[(f32, SoundEffect)]) -> Vec<SoundEffect> {
+        cues.iter().map(|(_, effect)| *effect).collect()
+    }
+
+    #[test]
+    fn test_bundled_sounds_are_wav_files() {
+        for effect in SoundEffect::ALL {
+            let bytes = effect.bytes();
+            assert_eq!(&bytes[..4], b"RIFF", "{:?}", effect);
+            assert_eq!(&bytes[8..12], b"WAVE", "{:?}", effect);
+        }
+    }
+
+    #[test]
+    fn test_mixer_scales_by_master_and_channel() {
+        let mut mixer = Mixer {
+            master: 0.5,
+            ..Mixer::default()
+        };
+        mixer.set_channel_volume(Channel::Table, 0.5);
+        mixer.set_channel_volume(Channel::Dice, 3.0);
+        assert_eq!(mixer.volume(SoundEffect::Bid), 0.25);
+        assert_eq!(mixer.volume(SoundEffect::DiceShake), 0.5);
+        mixer.muted = true;
+        assert_eq!(mixer.volume(SoundEffect::Victory), 0.0);
+    }
+
+    #[test]
+    fn test_bids_calls_and_deals_make_sounds() {
+        let mut game = Game::with_players(2, Rules::default());
+        game.phase = Phase::Dealing;
+        let dealt = advance_phase(&game).unwrap();
+        assert_eq!(sound_cues(&game, &dealt), vec![(0.0, SoundEffect::DiceShake)]);
+
+        let bid = PlayerAction {
+            action: Action::Bet,
+            bet: Some((1, 2)),
+        };
+        let after_bid = take_action(&dealt, &bid).unwrap();
+        assert_eq!(cue_effects(&sound_cues(&dealt, &after_bid)), [SoundEffect::Bid]);
+
+        let call = PlayerAction {
+            action: Action::Call,
+            bet: None,
+        };
+        let after_call = take_action(&after_bid, &call).unwrap();
+        let cues = sound_cues(&after_bid, &after_call);
+        assert_eq!(cue_effects(&cues), [SoundEffect::Call, SoundEffect::Reveal, SoundEffect::DieLost]);
+        assert!(cues.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn test_last_call_plays_elimination_then_victory() {
+        let mut game = Game::with_players(2, Rules { ones_wild: false, starting_dice: 1 });
+        game.player_dice[0] = [2, 0, 0, 0, 0];
+        game.player_dice[1] = [3, 0, 0, 0, 0];
+        let bid = PlayerAction {
+            action: Action::Bet,
+            bet: Some((2, 6)),
+        };
+        let after_bid = take_action(&game, &bid).unwrap();
+        let call = PlayerAction {
+            action: Action::Call,
+            bet: None,
+        };
+        let after_call = take_action(&after_bid, &call).unwrap();
+
+        let cues = sound_cues(&after_bid, &after_call);
+        let effects = cue_effects(&cues);
+        assert_eq!(&effects[3..], [SoundEffect::Elimination, SoundEffect::Victory]);
+        assert!(cues[3].0 < cues[4].0);
+    }
+}
@@ -0,0 +1,81 @@
+// Game time shared by everything that waits: AI move delays and turn limits. It only moves
+// when it is ticked, so tests can step it by hand instead of sleeping.
+
+/// Seconds of play since the clock was started
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Clock {
+    now: f32,
+}
+
+impl Clock {
+    /// Moves the clock on by one frame's worth of time
+    pub fn tick(&mut self, seconds: f32) {
+        self.now += seconds.max(0.0);
+    }
+
+    pub fn now(&self) -> f32 {
+        self.now
+    }
+
+    /// Seconds since an earlier reading of this clock
+    pub fn since(&self, earlier: f32) -> f32 {
+        self.now - earlier
+    }
+}
+
+/// Counts down one player's turn against the time limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TurnTimer {
+    pub player: u8,
+    pub started: f32,
+    pub limit: f32,
+}
+
+impl TurnTimer {
+    pub fn start(clock: &Clock, player: u8, limit: f32) -> Self {
+        Self {
+            player,
+            started: clock.now(),
+            limit,
+        }
+    }
+
+    /// Share of the turn left, from 1 at the start down to 0
+    pub fn remaining(&self, clock: &Clock) -> f32 {
+        if self.limit <= 0.0 {
+            return 0.0;
+        }
+        (1.0 - clock.since(self.started) / self.limit).clamp(0.0, 1.0)
+    }
+
+    pub fn is_expired(&self, clock: &Clock) -> bool {
+        clock.since(self.started) >= self.limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_only_moves_forward() {
+        let mut clock = Clock::default();
+        clock.tick(0.5);
+        clock.tick(-1.0);
+        assert_eq!(clock.now(), 0.5);
+        assert_eq!(clock.since(0.25), 0.25);
+    }
+
+    #[test]
+    fn test_turn_timer_runs_down() {
+        let mut clock = Clock::default();
+        clock.tick(3.0);
+        let timer = TurnTimer::start(&clock, 2, 10.0);
+        clock.tick(2.5);
+        assert!((timer.remaining(&clock) - 0.75).abs() < 0.001);
+        assert!(!timer.is_expired(&clock));
+        clock.tick(7.5);
+        assert!(timer.is_expired(&clock));
+        assert_eq!(timer.remaining(&clock), 0.0);
+    }
+}
@@ -1,68 +1,141 @@
-use macroquad::rand::gen_range;
 use macroquad::prelude::*;
 
-/// Rolls a dice and returns a random value from 1 to 6
-pub fn roll_dice() -> u8 {
-    gen_range(1, 7)
+/// Seeded random numbers for dealing, so a game's rolls can be reproduced from its seed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceRng {
+    state: u64,
+    script: Vec<u8>, // Rolls to hand out before any random ones, last roll first
 }
 
-/// Draws dots on a dice based on the given value (1-6)
-pub fn draw_dice_dots(x: f32, y: f32, value: u8) {
-    match value {
-        1 => {
-            // Center dot
-            draw_circle(x, y, 8.0, BLACK);
-        }
-        2 => {
-            // Top-left and bottom-right
-            draw_circle(x - 25.0, y - 25.0, 8.0, BLACK);
-            draw_circle(x + 25.0, y + 25.0, 8.0, BLACK);
+impl DiceRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+            script: Vec::new(),
         }
-        3 => {
-            // Top-left, center, and bottom-right
-            draw_circle(x - 25.0, y - 25.0, 8.0, BLACK);
-            draw_circle(x, y, 8.0, BLACK);
-            draw_circle(x + 25.0, y + 25.0, 8.0, BLACK);
-        }
-        4 => {
-            // All four corners
-            draw_circle(x - 25.0, y - 25.0, 8.0, BLACK);
-            draw_circle(x + 25.0, y - 25.0, 8.0, BLACK);
-            draw_circle(x - 25.0, y + 25.0, 8.0, BLACK);
-            draw_circle(x + 25.0, y + 25.0, 8.0, BLACK);
-        }
-        5 => {
-            // All four corners plus center
-            draw_circle(x - 25.0, y - 25.0, 8.0, BLACK);
-            draw_circle(x + 25.0, y - 25.0, 8.0, BLACK);
-            draw_circle(x, y, 8.0, BLACK);
-            draw_circle(x - 25.0, y + 25.0, 8.0, BLACK);
-            draw_circle(x + 25.0, y + 25.0, 8.0, BLACK);
+    }
+
+    /// Deals the given rolls in order before falling back to random ones
+    pub fn scripted(rolls: &[u8]) -> Self {
+        Self {
+            state: 0,
+            script: rolls.iter().rev().copied().collect(),
         }
-        6 => {
-            // Two columns of three dots
-            draw_circle(x - 25.0, y - 25.0, 8.0, BLACK);
-            draw_circle(x + 25.0, y - 25.0, 8.0, BLACK);
-            draw_circle(x - 25.0, y, 8.0, BLACK);
-            draw_circle(x + 25.0, y, 8.0, BLACK);
-            draw_circle(x - 25.0, y + 25.0, 8.0, BLACK);
-            draw_circle(x + 25.0, y + 25.0, 8.0, BLACK);
+    }
+
+    // SplitMix64
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// A seed for a new game, taken from the clock
+pub fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+/// Rolls a dice and returns a random value from 1 to 6
+pub fn roll_dice(rng: &mut DiceRng) -> u8 {
+    match rng.script.pop() {
+        Some(roll) => roll,
+        None => (rng.next_u64() % 6) as u8 + 1,
+    }
+}
+
+// Proportions of a die face, as fractions of the die's width
+const PIP_SPREAD: f32 = 0.27; // From the centre to a corner pip, along each axis
+const PIP_RADIUS: f32 = 0.09;
+const CORNER_RADIUS: f32 = 0.15;
+
+/// What is drawn on a die's faces
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiceFaces {
+    Pips,
+    Numerals,
+    Images(Box<[Texture2D; 6]>), // One picture per face, 1 to 6
+}
+
+/// How one player's dice are drawn
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiceStyle {
+    pub body: Color,
+    pub marks: Color, // Pips, numerals and the question mark on hidden dice
+    pub faces: DiceFaces,
+}
+
+/// Where the pips sit on a face, in steps of `PIP_SPREAD` from the centre
+pub fn pip_positions(value: u8) -> &'static [(f32, f32)] {
+    match value {
+        1 => &[(0.0, 0.0)],
+        2 => &[(-1.0, -1.0), (1.0, 1.0)],
+        3 => &[(-1.0, -1.0), (0.0, 0.0), (1.0, 1.0)],
+        4 => &[(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)],
+        5 => &[(-1.0, -1.0), (1.0, -1.0), (0.0, 0.0), (-1.0, 1.0), (1.0, 1.0)],
+        6 => &[(-1.0, -1.0), (1.0, -1.0), (-1.0, 0.0), (1.0, 0.0), (-1.0, 1.0), (1.0, 1.0)],
+        _ => &[],
+    }
+}
+
+fn draw_rounded_rectangle(x: f32, y: f32, w: f32, h: f32, radius: f32, color: Color) {
+    draw_rectangle(x + radius, y, w - 2.0 * radius, h, color);
+    draw_rectangle(x, y + radius, radius, h - 2.0 * radius, color);
+    draw_rectangle(x + w - radius, y + radius, radius, h - 2.0 * radius, color);
+    for (corner_x, corner_y) in [(x + radius, y + radius), (x + w - radius, y + radius), (x + radius, y + h - radius), (x + w - radius, y + h - radius)] {
+        draw_circle(corner_x, corner_y, radius, color);
+    }
+}
+
+/// Draws a die `size` pixels wide centred on (x, y), showing `value`, or a question mark
+/// when the face is hidden
+pub fn draw_die(x: f32, y: f32, size: f32, value: Option<u8>, style: &DiceStyle) {
+    draw_rounded_rectangle(x - size / 2.0, y - size / 2.0, size, size, size * CORNER_RADIUS, style.body);
+
+    let Some(value) = value.filter(|value| (1..=6).contains(value)) else {
+        draw_die_text("?", x, y, size, style.marks);
+        return;
+    };
+    match &style.faces {
+        DiceFaces::Pips => {
+            for (step_x, step_y) in pip_positions(value) {
+                draw_circle(x + step_x * PIP_SPREAD * size, y + step_y * PIP_SPREAD * size, PIP_RADIUS * size, style.marks);
+            }
         }
-        _ => {
-            // Invalid value, draw nothing
+        DiceFaces::Numerals => draw_die_text(&value.to_string(), x, y, size, style.marks),
+        DiceFaces::Images(faces) => {
+            // Inset so the picture doesn't cover the rounded corners
+            let inset = size * CORNER_RADIUS / 2.0;
+            let params = DrawTextureParams {
+                dest_size: Some(vec2(size - 2.0 * inset, size - 2.0 * inset)),
+                ..Default::default()
+            };
+            let tint = Color::new(1.0, 1.0, 1.0, style.body.a);
+            draw_texture_ex(&faces[value as usize - 1], x - size / 2.0 + inset, y - size / 2.0 + inset, tint, params);
         }
     }
 }
 
+fn draw_die_text(text: &str, x: f32, y: f32, size: f32, color: Color) {
+    let font_size = size * 0.6;
+    let dimensions = measure_text(text, None, font_size as u16, 1.0);
+    draw_text(text, x - dimensions.width / 2.0, y + dimensions.offset_y / 2.0, font_size, color);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_roll_dice_range() {
+        let mut rng = DiceRng::new(1);
         for _ in 0..1000 {
-            let result = roll_dice();
-            assert!(result >= 1 && result <= 6, "Dice roll {} is not in range 1-6", result);
+            let result = roll_dice(&mut rng);
+            assert!((1..=6).contains(&result), "Dice roll {} is not in range 1-6", result);
         }
     }
 
@@ -70,9 +143,10 @@ mod tests {
     fn test_roll_dice_distribution() {
         let mut counts = [0; 6];
         let num_rolls = 10000;
+        let mut rng = DiceRng::new(random_seed());
         
         for _ in 0..num_rolls {
-            let result = roll_dice();
+            let result = roll_dice(&mut rng);
             counts[(result - 1) as usize] += 1;
         }
         
@@ -95,4 +169,39 @@ mod tests {
             );
         }
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_same_seed_rolls_the_same_dice() {
+        let mut first = DiceRng::new(2024);
+        let mut second = DiceRng::new(2024);
+        let first_rolls: Vec<u8> = (0..20).map(|_| roll_dice(&mut first)).collect();
+        let second_rolls: Vec<u8> = (0..20).map(|_| roll_dice(&mut second)).collect();
+        assert_eq!(first_rolls, second_rolls);
+
+        let mut other = DiceRng::new(2025);
+        let other_rolls: Vec<u8> = (0..20).map(|_| roll_dice(&mut other)).collect();
+        assert_ne!(first_rolls, other_rolls);
+    }
+
+    #[test]
+    fn test_pips_match_the_face_and_stay_on_the_die() {
+        for value in 1..=6 {
+            let pips = pip_positions(value);
+            assert_eq!(pips.len(), value as usize);
+            for (step_x, step_y) in pips {
+                let reach = step_x.abs().max(step_y.abs()) * PIP_SPREAD + PIP_RADIUS;
+                assert!(reach < 0.5 - CORNER_RADIUS / 4.0, "pip on {} runs off the die", value);
+            }
+        }
+        assert!(pip_positions(7).is_empty());
+    }
+
+    #[test]
+    fn test_scripted_rolls_come_first() {
+        let mut rng = DiceRng::scripted(&[6, 1, 3]);
+        assert_eq!(roll_dice(&mut rng), 6);
+        assert_eq!(roll_dice(&mut rng), 1);
+        assert_eq!(roll_dice(&mut rng), 3);
+        assert!((1..=6).contains(&roll_dice(&mut rng)));
+    }
+}
@@ -0,0 +1,333 @@
+// Every game event written as newline-delimited JSON, one file per game, for analysing how
+// people and AI seats bid and bluff. The schema is described in the README under Event Logs.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::dice::{random_seed, DiceRng};
+use crate::game::{Game, GameEvent, Phase};
+use crate::settings::Settings;
+use crate::storage;
+
+/// Logs are written to this folder in the data directory, as `<game_id>.ndjson`
+pub const EVENT_LOG_DIR: &str = "event_logs";
+
+/// Bumped whenever a field is renamed or removed, or its meaning changes
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Where a logged game was played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Gui,
+    SelfPlay,
+}
+
+impl Source {
+    fn name(self) -> &'static str {
+        match self {
+            Source::Gui => "gui",
+            Source::SelfPlay => "self_play",
+        }
+    }
+}
+
+/// An open log for one game
+pub struct EventLog {
+    pub game_id: String,
+    pub path: PathBuf,
+    writer: BufWriter<File>,
+    seq: u64, // Number of the next line, counting from 0
+}
+
+impl EventLog {
+    /// Starts a log in `dir` for a game that has just been dealt, writing its players and
+    /// opening hands
+    pub fn create(dir: &Path, source: Source, game: &Game, settings: &Settings) -> Result<Self, String> {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+        let game_id = new_game_id();
+        let path = dir.join(format!("{}.ndjson", game_id));
+        let file = File::create(&path).map_err(|e| format!("Could not create {}: {}", path.display(), e))?;
+
+        let mut log = Self {
+            game_id,
+            path,
+            writer: BufWriter::new(file),
+            seq: 0,
+        };
+        let mut lines = vec![("game_start", start_fields(source, game, settings))];
+        if game.phase == Phase::Bidding {
+            lines.push(("deal", deal_fields(game)));
+        }
+        log.write(&lines)?;
+        Ok(log)
+    }
+
+    /// Writes whatever happened between two states of the table
+    pub fn record(&mut self, before: &Game, after: &Game) -> Result<(), String> {
+        self.write(&event_fields(before, after))
+    }
+
+    /// Notes that a casual game was rewound to an earlier state, so the moves logged since
+    /// then were taken back
+    fn record_takeback(&mut self, game: &Game) -> Result<(), String> {
+        self.write(&[("takeback", format!(r#""round":{},"events_kept":{}"#, game.round, game.events.len()))])
+    }
+
+    fn write(&mut self, lines: &[(&str, String)]) -> Result<(), String> {
+        let time_ms = unix_time_ms();
+        for (event, fields) in lines {
+            let line = format!(
+                r#"{{"schema":{},"game_id":"{}","seq":{},"time_ms":{},"event":"{}",{}}}"#,
+                SCHEMA_VERSION, self.game_id, self.seq, time_ms, event, fields
+            );
+            writeln!(self.writer, "{}", line).map_err(|e| format!("Could not write {}: {}", self.path.display(), e))?;
+            self.seq += 1;
+        }
+        self.writer.flush().map_err(|e| format!("Could not write {}: {}", self.path.display(), e))
+    }
+}
+
+/// Opens a log in the data directory for a newly dealt game, if the settings ask for one.
+/// A log that can't be opened is reported and the game goes on without it.
+pub fn start_log(source: Source, game: &Game, settings: &Settings) -> Option<EventLog> {
+    if !settings.event_log {
+        return None;
+    }
+    storage::data_file(EVENT_LOG_DIR)
+        .and_then(|dir| EventLog::create(&dir, source, game, settings))
+        .map_err(|e| println!("Event log error: {}", e))
+        .ok()
+}
+
+/// Records a change to the table, closing the log if it can no longer be written
+pub fn log_transition(log: &mut Option<EventLog>, before: &Game, after: &Game) {
+    if let Some(Err(e)) = log.as_mut().map(|log| log.record(before, after)) {
+        println!("Event log error: {}", e);
+        *log = None;
+    }
+}
+
+/// Records that the table was rewound to `game`, closing the log if it can no longer be written
+pub fn log_takeback(log: &mut Option<EventLog>, game: &Game) {
+    if let Some(Err(e)) = log.as_mut().map(|log| log.record_takeback(game)) {
+        println!("Event log error: {}", e);
+        *log = None;
+    }
+}
+
+fn unix_time_ms() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis())
+}
+
+/// A random (version 4) UUID. Games started in the same instant still get different ids,
+/// as each one moves the seed on.
+fn new_game_id() -> String {
+    static GAMES_STARTED: AtomicU64 = AtomicU64::new(0);
+    let started = GAMES_STARTED.fetch_add(1, Ordering::Relaxed);
+    let mut rng = DiceRng::new(random_seed() ^ (u64::from(std::process::id()) << 32) ^ started.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    let mut bytes = [0; 16];
+    bytes[..8].copy_from_slice(&rng.next_u64().to_be_bytes());
+    bytes[8..].copy_from_slice(&rng.next_u64().to_be_bytes());
+    format_uuid(bytes)
+}
+
+fn format_uuid(mut bytes: [u8; 16]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40; // Version 4
+    bytes[8] = (bytes[8] & 0x3f) | 0x80; // RFC 4122 variant
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// A string as a JSON string literal
+fn json_string(text: &str) -> String {
+    let mut json = String::from('"');
+    for character in text.chars() {
+        match character {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            character if character.is_control() => json.push_str(&format!("\\u{:04x}", character as u32)),
+            character => json.push(character),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn json_list<T: ToString>(items: impl IntoIterator<Item = T>) -> String {
+    let items: Vec<String> = items.into_iter().map(|item| item.to_string()).collect();
+    format!("[{}]", items.join(","))
+}
+
+fn start_fields(source: Source, game: &Game, settings: &Settings) -> String {
+    let players = (0..game.player_count as usize).map(|player| {
+        format!(
+            r#"{{"player":{},"name":{},"seat":"{}"}}"#,
+            player,
+            json_string(&game.player_names[player]),
+            settings.seats[player].name()
+        )
+    });
+    format!(
+        r#""source":"{}","seed":{},"rules":{{"ones_wild":{},"starting_dice":{}}},"players":{}"#,
+        source.name(),
+        game.seed,
+        game.rules.ones_wild,
+        game.rules.starting_dice,
+        json_list(players)
+    )
+}
+
+/// Every hand at the table, including the empty hands of players who are out
+fn deal_fields(game: &Game) -> String {
+    let hands = (0..game.player_count as usize).map(|player| {
+        let dice_count = game.current_player_dice_count[player] as usize;
+        json_list(&game.player_dice[player][..dice_count])
+    });
+    format!(r#""round":{},"hands":{}"#, game.round, json_list(hands))
+}
+
+/// The log lines for what happened between two states of the table, as (event, fields)
+fn event_fields(before: &Game, after: &Game) -> Vec<(&'static str, String)> {
+    let mut lines = Vec::new();
+    let round = before.round;
+    let new_events = after.events.get(before.events.len()..).unwrap_or_default();
+    for event in new_events {
+        match event {
+            GameEvent::Bet { player, dice_count, face_value } => lines.push((
+                "bid",
+                format!(r#""round":{},"player":{},"dice_count":{},"face_value":{}"#, round, player, dice_count, face_value),
+            )),
+            GameEvent::Call(resolution) => {
+                let (bidder, dice_count, face_value) = resolution.bet;
+                lines.push((
+                    "call",
+                    format!(
+                        r#""round":{},"player":{},"bid":{{"player":{},"dice_count":{},"face_value":{}}}"#,
+                        round, resolution.caller, bidder, dice_count, face_value
+                    ),
+                ));
+                lines.push((
+                    "resolution",
+                    format!(
+                        r#""round":{},"actual_count":{},"wild_count":{},"bid_holds":{},"losing_player":{}"#,
+                        round,
+                        resolution.actual_count,
+                        resolution.wild_count,
+                        resolution.actual_count >= dice_count,
+                        resolution.losing_player
+                    ),
+                ));
+                lines.push(("die_lost", die_lost_fields(round, resolution.losing_player, after)));
+            }
+            GameEvent::TimedOut { player, .. } => {
+                lines.push(("timeout", format!(r#""round":{},"player":{}"#, round, player)));
+                lines.push(("die_lost", die_lost_fields(round, *player, after)));
+            }
+            GameEvent::Eliminated(elimination) => {
+                lines.push(("elimination", format!(r#""round":{},"player":{}"#, elimination.round, elimination.player)));
+            }
+            GameEvent::Won { player } => lines.push((
+                "game_end",
+                format!(
+                    r#""round":{},"winner":{},"finishing_order":{},"takebacks":{}"#,
+                    round,
+                    player,
+                    json_list(after.finishing_order()),
+                    after.takebacks
+                ),
+            )),
+        }
+    }
+    if before.phase == Phase::Dealing && after.phase == Phase::Bidding {
+        lines.push(("deal", deal_fields(after)));
+    }
+    lines
+}
+
+fn die_lost_fields(round: u32, player: u8, after: &Game) -> String {
+    format!(r#""round":{},"player":{},"dice_left":{}"#, round, player, after.current_player_dice_count[player as usize])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::{advance_phase, take_action, Action, PlayerAction, Rules};
+
+    #[test]
+    fn test_game_ids_are_version_4_uuids() {
+        assert_eq!(format_uuid([0xff; 16]), "ffffffff-ffff-4fff-bfff-ffffffffffff");
+        assert_eq!(format_uuid([0; 16]), "00000000-0000-4000-8000-000000000000");
+        assert_ne!(new_game_id(), new_game_id());
+    }
+
+    #[test]
+    fn test_strings_are_escaped() {
+        assert_eq!(json_string(r#"Ana "the \ bluffer""#), r#""Ana \"the \\ bluffer\"""#);
+        assert_eq!(json_string("a\u{1}b"), r#""a\u0001b""#);
+    }
+
+    #[test]
+    fn test_a_game_is_logged_from_deal_to_end() {
+        let dir = std::env::temp_dir().join(format!("rusty-dice-events-{}", std::process::id()));
+        let mut settings = Settings::new();
+        settings.player_count = 2;
+        settings.player_names[0] = "Ana \"Ace\"".to_string();
+        let mut game = settings.new_game();
+        game.rules = Rules { ones_wild: false, starting_dice: 1 };
+        game.current_player_dice_count = [1; 8];
+        game.player_dice[0] = [2, 0, 0, 0, 0];
+        game.player_dice[1] = [3, 0, 0, 0, 0];
+
+        let mut log = EventLog::create(&dir, Source::Gui, &game, &settings).unwrap();
+        let actions = [
+            PlayerAction { action: Action::Bet, bet: Some((2, 6)) },
+            PlayerAction { action: Action::Call, bet: None },
+        ];
+        for action in &actions {
+            let after = take_action(&game, action).unwrap();
+            log.record(&game, &after).unwrap();
+            game = after;
+        }
+        let path = log.path.clone();
+        drop(log);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        let events: Vec<&str> = lines.iter().map(|line| line.split(r#""event":""#).nth(1).unwrap().split('"').next().unwrap()).collect();
+        assert_eq!(events, ["game_start", "deal", "bid", "call", "resolution", "die_lost", "elimination", "game_end"]);
+        for (seq, line) in lines.iter().enumerate() {
+            assert!(line.starts_with(&format!(r#"{{"schema":1,"game_id":"{}","seq":{},"#, path.file_stem().unwrap().to_str().unwrap(), seq)));
+            assert!(line.ends_with('}'));
+        }
+        assert!(lines[0].contains(r#""name":"Ana \"Ace\"","seat":"human""#));
+        assert!(lines[1].ends_with(r#""round":1,"hands":[[2],[3]]}"#));
+        assert!(lines[2].ends_with(r#""round":1,"player":0,"dice_count":2,"face_value":6}"#));
+        assert!(lines[4].contains(r#""actual_count":0,"wild_count":0,"bid_holds":false,"losing_player":0"#));
+        assert!(lines[5].ends_with(r#""player":0,"dice_left":0}"#));
+        assert!(lines[7].contains(r#""winner":1,"finishing_order":[1,0]"#));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_the_next_deal_is_logged_with_every_hand() {
+        let settings = Settings::new();
+        let game = settings.new_game();
+        let call = PlayerAction { action: Action::Call, bet: None };
+        let bid = PlayerAction { action: Action::Bet, bet: Some((1, 2)) };
+        let called = take_action(&take_action(&game, &bid).unwrap(), &call).unwrap();
+        let dealing = advance_phase(&called).unwrap();
+        let dealt = advance_phase(&dealing).unwrap();
+
+        assert!(event_fields(&called, &dealing).is_empty());
+        let lines = event_fields(&dealing, &dealt);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].0, "deal");
+        assert!(lines[0].1.starts_with(r#""round":2,"hands":[["#));
+    }
+}
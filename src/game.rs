@@ -1,9 +1,10 @@
 // Game state and player management for Rusty Dice
 
-use crate::dice::roll_dice;
+use crate::dice::{roll_dice, DiceRng};
+use crate::i18n::tr;
 
-const MAX_PLAYERS: usize = 8;
-const DICE_PER_PLAYER: usize = 5;
+pub const MAX_PLAYERS: usize = 8;
+pub const DICE_PER_PLAYER: usize = 5;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Action {
@@ -17,70 +18,427 @@ pub struct PlayerAction {
     pub bet: Option<(u8, u8)> // (dice_count, face_value)
 }
 
+/// Rule variants chosen when the game is set up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    pub ones_wild: bool,  // 1s count towards bets on any other face
+    pub starting_dice: u8, // Dice each player starts with, up to DICE_PER_PLAYER
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            ones_wild: false,
+            starting_dice: DICE_PER_PLAYER as u8,
+        }
+    }
+}
+
+/// Where the game is in the current round's lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Bidding,   // Players take turns betting until someone calls
+    Revealing, // The called round's hands stay on the table until the UI moves on
+    Dealing,   // Dice are rolled for the next round
+}
+
+/// A snapshot of every hand from a completed round
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundHands {
+    pub dice_count: [u8; MAX_PLAYERS],
+    pub player_dice: [[u8; DICE_PER_PLAYER]; MAX_PLAYERS],
+}
+
+impl RoundHands {
+    /// Dice in these hands that count towards a face, and how many of those were wild 1s
+    pub fn count_matching(&self, face_value: u8, rules: &Rules) -> (u8, u8) {
+        let ones_are_wild = rules.ones_wild && face_value != 1;
+        let mut matching = 0;
+        let mut wild = 0;
+        for (hand, &dice_count) in self.player_dice.iter().zip(&self.dice_count) {
+            for &die in &hand[..dice_count as usize] {
+                if die == face_value {
+                    matching += 1;
+                } else if ones_are_wild && die == 1 {
+                    matching += 1;
+                    wild += 1;
+                }
+            }
+        }
+        (matching, wild)
+    }
+}
+
+/// A player losing their last die
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elimination {
+    pub player: u8,
+    pub round: u32, // Round the die was lost in, counting from 1
+}
+
+/// Something that happened during a game, kept in order so stats can be worked out afterwards
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    Bet { player: u8, dice_count: u8, face_value: u8 },
+    Call(RoundResolution),
+    TimedOut { player: u8, hands: RoundHands }, // Ran out of time and lost a die, ending the round
+    Eliminated(Elimination),
+    Won { player: u8 },
+}
+
+/// What the engine plays for a player whose turn time runs out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutAction {
+    MinimumRaise, // The smallest legal raise, or a call when there is none
+    Call,         // Calls the last bet, or opens with the smallest bet
+    LoseDie,      // Loses a die and the round starts over
+}
+
+impl TimeoutAction {
+    pub const ALL: [TimeoutAction; 3] = [TimeoutAction::MinimumRaise, TimeoutAction::Call, TimeoutAction::LoseDie];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TimeoutAction::MinimumRaise => "minimum_raise",
+            TimeoutAction::Call => "call",
+            TimeoutAction::LoseDie => "lose_die",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    pub fn label(self) -> String {
+        tr(&format!("timeout.{}", self.name()))
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&action| action == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// How a round was settled when its last bet was called
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundResolution {
+    pub hands: RoundHands, // Hands as they were when the call was made
+    pub bet: (u8, u8, u8), // (player_index, dice_count, face_value) of the called bet
+    pub caller: u8,
+    pub actual_count: u8, // Dice across all hands that matched the bet face value, wilds included
+    pub wild_count: u8,   // How many of those were wild 1s
+    pub losing_player: u8,
+}
+
+impl RoundResolution {
+    pub fn face_value(&self) -> u8 {
+        self.bet.2
+    }
+
+    /// Whether a die showing this face counted towards the bet
+    pub fn counts_towards_bet(&self, face_value: u8, rules: &Rules) -> bool {
+        face_value == self.face_value() || (rules.ones_wild && face_value == 1)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Game {
     pub player_count: u8,
+    pub rules: Rules,
     pub current_player: u8,
     pub round_starter: u8,
+    pub phase: Phase,
     pub winner: Option<u8>,
+    pub round: u32, // Current round, counting from 1
+    pub eliminations: Vec<Elimination>, // Players who are out, first out first
     pub player_names: [String; MAX_PLAYERS],
+    pub is_human: [bool; MAX_PLAYERS], // Seats played from this machine rather than by the AI
     pub current_player_dice_count: [u8; MAX_PLAYERS],
     pub player_dice: [[u8; DICE_PER_PLAYER]; MAX_PLAYERS],
     pub bets: Vec<(u8, u8, u8)>, // (player_index, dice_count, face_value)
+    pub last_round: Option<RoundResolution>, // Kept so the UI can show the hands after the reroll
+    pub events: Vec<GameEvent>,
+    pub seed: u64, // Where the dealing started from, so the game can be reproduced
+    pub rng: DiceRng,
+    pub takebacks: u32, // Moves the human took back in a casual game
 }
 
 pub fn roll_all_dice(game: &mut Game) {
     for player in 0..game.player_count as usize {
         let dice_count = game.current_player_dice_count[player] as usize;
         for die in 0..dice_count {
-            game.player_dice[player][die] = roll_dice();
+            game.player_dice[player][die] = roll_dice(&mut game.rng);
         }
     }
 }
 
 impl Game {
+    /// The default 4 player table; games are set up through `with_players`
+    #[cfg(test)]
     pub fn new() -> Self {
+        Self::with_players(4, Rules::default())
+    }
+
+    /// Creates a game for 2-8 players using the given rules
+    pub fn with_players(player_count: u8, rules: Rules) -> Self {
+        let player_count = player_count.clamp(2, MAX_PLAYERS as u8);
+        let rules = Rules {
+            starting_dice: rules.starting_dice.clamp(1, DICE_PER_PLAYER as u8),
+            ..rules
+        };
+
         let mut player_names = [(); MAX_PLAYERS].map(|_| String::new());
-        for i in 0..4 {
-            player_names[i] = format!("Player {}", i + 1);
+        for (i, name) in player_names.iter_mut().take(player_count as usize).enumerate() {
+            *name = format!("Player {}", i + 1);
         }
         
-        let current_player_dice_count = [5; MAX_PLAYERS]; // Start with 5 dice each
+        // Everyone starts with the same number of dice, empty seats with none
+        let mut current_player_dice_count = [0; MAX_PLAYERS];
+        current_player_dice_count[..player_count as usize].fill(rules.starting_dice);
+
+        let mut is_human = [false; MAX_PLAYERS];
+        is_human[0] = true; // Player 0 is the local human by default
         
         Self {
-            player_count: 4,
+            player_count,
+            rules,
             current_player: 0, // Start with player 0
             round_starter: 0, // Start with player 0 as round starter
+            phase: Phase::Bidding,
             winner: None,
+            round: 1,
+            eliminations: Vec::new(),
             player_names,
+            is_human,
             current_player_dice_count,
             player_dice: [[0; DICE_PER_PLAYER]; MAX_PLAYERS],
             bets: Vec::new(), // Initialize bets array
+            last_round: None,
+            events: Vec::new(),
+            seed: 0,
+            rng: DiceRng::new(0),
+            takebacks: 0,
+        }
+    }
+
+    /// Restarts the dealing from a seed
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = DiceRng::new(seed);
+    }
+
+    /// The table as one player sees it: every other hand blanked out and the dealer's
+    /// seed hidden, so nothing worked out from it can use the other players' dice
+    pub fn seen_by(&self, player: u8) -> Game {
+        let mut view = self.clone();
+        for (seat, hand) in view.player_dice.iter_mut().enumerate() {
+            if seat != player as usize {
+                *hand = [0; DICE_PER_PLAYER];
+            }
+        }
+        view.set_seed(0);
+        view
+    }
+
+    /// Every hand from the last round to end, whether it was called or someone ran out of time
+    pub fn previous_round_hands(&self) -> Option<&RoundHands> {
+        self.events.iter().rev().find_map(|event| match event {
+            GameEvent::Call(resolution) => Some(&resolution.hands),
+            GameEvent::TimedOut { hands, .. } => Some(hands),
+            _ => None,
+        })
+    }
+
+    /// Total dice still in play across all players
+    pub fn total_dice(&self) -> u8 {
+        self.current_player_dice_count[..self.player_count as usize].iter().sum()
+    }
+
+    /// Seats from first to last place, worked out from the order players ran out of dice.
+    /// Players still in an unfinished game are left out.
+    pub fn finishing_order(&self) -> Vec<u8> {
+        let mut order: Vec<u8> = self.winner.into_iter().collect();
+        order.extend(self.eliminations.iter().rev().map(|elimination| elimination.player));
+        order
+    }
+
+    /// The round a player lost their last die in, if they are out
+    pub fn elimination_round(&self, player: u8) -> Option<u32> {
+        self.eliminations
+            .iter()
+            .find(|elimination| elimination.player == player)
+            .map(|elimination| elimination.round)
+    }
+
+    /// Number of seats played by humans at this machine
+    pub fn human_count(&self) -> usize {
+        self.is_human[..self.player_count as usize].iter().filter(|&&human| human).count()
+    }
+}
+
+/// Whether a (dice_count, face_value) bet is higher than the last bet of the round.
+/// Any bet is a raise when nobody has bet yet.
+pub fn is_raise(game: &Game, bet: (u8, u8)) -> bool {
+    let (dice_count, face_value) = bet;
+    match game.bets.last() {
+        Some((_, last_dice_count, last_face_value)) => {
+            dice_count > *last_dice_count || (dice_count == *last_dice_count && face_value > *last_face_value)
+        }
+        None => true,
+    }
+}
+
+/// Whether a (dice_count, face_value) bet can be placed right now
+pub fn is_legal_bet(game: &Game, bet: (u8, u8)) -> bool {
+    let (dice_count, face_value) = bet;
+    dice_count > 0 && (1..=6).contains(&face_value) && is_raise(game, bet)
+}
+
+/// Every bet that could be placed right now, lowest first. Bets on more dice than
+/// are in play can never be true, so they are left out.
+pub fn legal_raises(game: &Game) -> Vec<(u8, u8)> {
+    let mut raises = Vec::new();
+    for dice_count in 1..=game.total_dice() {
+        for face_value in 1..=6 {
+            if is_legal_bet(game, (dice_count, face_value)) {
+                raises.push((dice_count, face_value));
+            }
+        }
+    }
+    raises
+}
+
+/// The lowest bet that beats the last one
+pub fn minimum_raise(game: &Game) -> Option<(u8, u8)> {
+    legal_raises(game).first().copied()
+}
+
+/// Moves a finished round on to the next phase: from revealing to dealing, and from
+/// dealing to bidding with freshly rolled dice
+pub fn advance_phase(game: &Game) -> Result<Game, String> {
+    let mut new_game = game.clone();
+
+    match game.phase {
+        Phase::Bidding => return Err("The round is still being bid on".to_string()),
+        Phase::Revealing => new_game.phase = Phase::Dealing,
+        Phase::Dealing => {
+            roll_all_dice(&mut new_game);
+            new_game.phase = Phase::Bidding;
+        }
+    }
+
+    Ok(new_game)
+}
+
+/// Takes a die from the player who lost the round, then sets up the next round: the
+/// betting is cleared and the next player with dice starts
+fn lose_die_and_end_round(game: &mut Game, losing_player: u8) {
+    // Make the losing player lose a die
+    if game.current_player_dice_count[losing_player as usize] > 0 {
+        game.current_player_dice_count[losing_player as usize] -= 1;
+        if game.current_player_dice_count[losing_player as usize] == 0 {
+            let elimination = Elimination {
+                player: losing_player,
+                round: game.round,
+            };
+            game.eliminations.push(elimination);
+            game.events.push(GameEvent::Eliminated(elimination));
+        }
+        
+        // Check if only one player has dice left
+        let mut players_with_dice = 0;
+        let mut last_player_with_dice = 0;
+        for player in 0..game.player_count as usize {
+            if game.current_player_dice_count[player] > 0 {
+                players_with_dice += 1;
+                last_player_with_dice = player;
+            }
+        }
+        
+        // If only one player has dice, they win
+        if players_with_dice == 1 {
+            game.winner = Some(last_player_with_dice as u8);
+            game.events.push(GameEvent::Won { player: last_player_with_dice as u8 });
+        }
+    }
+    
+    // Leave the dice on the table until the next round is dealt
+    game.phase = Phase::Revealing;
+    if game.winner.is_none() {
+        game.round += 1;
+    }
+    
+    // Clear the betting history for the next round
+    game.bets.clear();
+
+    // Advance the round starter to the next player, skipping those who are out
+    game.round_starter = (game.round_starter + 1) % game.player_count;
+    while game.winner.is_none() && game.current_player_dice_count[game.round_starter as usize] == 0 {
+        game.round_starter = (game.round_starter + 1) % game.player_count;
+    }
+    
+    // Set current player to the new round starter
+    game.current_player = game.round_starter;
+}
+
+/// Plays the default for the current player when their turn time runs out
+pub fn apply_timeout(game: &Game, timeout: TimeoutAction) -> Result<Game, String> {
+    let call = PlayerAction {
+        action: Action::Call,
+        bet: None,
+    };
+    let raise = minimum_raise(game).map(|bet| PlayerAction {
+        action: Action::Bet,
+        bet: Some(bet),
+    });
+
+    match timeout {
+        TimeoutAction::MinimumRaise => take_action(game, raise.as_ref().unwrap_or(&call)),
+        TimeoutAction::Call if !game.bets.is_empty() => take_action(game, &call),
+        TimeoutAction::Call => take_action(game, raise.as_ref().unwrap_or(&call)),
+        TimeoutAction::LoseDie => {
+            if game.phase != Phase::Bidding {
+                return Err("Actions can only be taken while bidding".to_string());
+            }
+            let mut new_game = game.clone();
+            let player = game.current_player;
+            new_game.events.push(GameEvent::TimedOut {
+                player,
+                hands: RoundHands {
+                    dice_count: game.current_player_dice_count,
+                    player_dice: game.player_dice,
+                },
+            });
+            lose_die_and_end_round(&mut new_game, player);
+            // Nothing was called, so there is nothing to reveal
+            new_game.phase = Phase::Dealing;
+            Ok(new_game)
         }
     }
 }
 
 pub fn take_action(game: &Game, action: &PlayerAction) -> Result<Game, String> {
     let mut new_game = game.clone();
+
+    if game.phase != Phase::Bidding {
+        return Err("Actions can only be taken while bidding".to_string());
+    }
     
     match action.action {
         Action::Call => {
             if let Some(last_bet) = new_game.bets.last() {
                 let (betting_player, bet_dice_count, bet_face_value) = last_bet;
+                let hands = RoundHands {
+                    dice_count: new_game.current_player_dice_count,
+                    player_dice: new_game.player_dice,
+                };
                 
-                // Count all dice across all players that match the bet face value
-                let mut total_matching_dice = 0;
-                for player in 0..new_game.player_count as usize {
-                    let dice_count = new_game.current_player_dice_count[player] as usize;
-                    for die in 0..dice_count {
-                        if new_game.player_dice[player][die] == *bet_face_value {
-                            total_matching_dice += 1;
-                        }
-                    }
-                }
+                // Count all dice across all players that match the bet face value,
+                // plus any 1s when they are wild
+                let (total_matching_dice, wild_dice) = hands.count_matching(*bet_face_value, &new_game.rules);
                 
                 // Determine if the bet was valid (dice count >= bet)
-                let bet_was_valid = total_matching_dice >= *bet_dice_count as usize;
+                let bet_was_valid = total_matching_dice >= *bet_dice_count;
                 
                 // Determine who loses a die
                 let losing_player = if bet_was_valid {
@@ -91,37 +449,19 @@ pub fn take_action(game: &Game, action: &PlayerAction) -> Result<Game, String> {
                     *betting_player
                 };
                 
-                // Make the losing player lose a die
-                if new_game.current_player_dice_count[losing_player as usize] > 0 {
-                    new_game.current_player_dice_count[losing_player as usize] -= 1;
-                    
-                    // Check if only one player has dice left
-                    let mut players_with_dice = 0;
-                    let mut last_player_with_dice = 0;
-                    for player in 0..new_game.player_count as usize {
-                        if new_game.current_player_dice_count[player] > 0 {
-                            players_with_dice += 1;
-                            last_player_with_dice = player;
-                        }
-                    }
-                    
-                    // If only one player has dice, they win
-                    if players_with_dice == 1 {
-                        new_game.winner = Some(last_player_with_dice as u8);
-                    }
-                }
-                
-                // Roll all dice for the next round
-                roll_all_dice(&mut new_game);
-                
-                // Clear the betting history for the next round
-                new_game.bets.clear();
+                // Keep the hands that settled the bet before anything changes
+                let resolution = RoundResolution {
+                    hands,
+                    bet: *last_bet,
+                    caller: new_game.current_player,
+                    actual_count: total_matching_dice,
+                    wild_count: wild_dice,
+                    losing_player,
+                };
+                new_game.events.push(GameEvent::Call(resolution.clone()));
+                new_game.last_round = Some(resolution);
 
-                // Advance the round starter to the next player
-                new_game.round_starter = (new_game.round_starter + 1) % new_game.player_count;
-                
-                // Set current player to the new round starter
-                new_game.current_player = new_game.round_starter;
+                lose_die_and_end_round(&mut new_game, losing_player);
             } else {
                 return Err("Cannot call when no bets have been made".to_string());
             }
@@ -129,7 +469,7 @@ pub fn take_action(game: &Game, action: &PlayerAction) -> Result<Game, String> {
         Action::Bet => {
             if let Some((dice_count, face_value)) = action.bet {
                 // Validate bet parameters
-                if face_value < 1 || face_value > 6 {
+                if !(1..=6).contains(&face_value) {
                     return Err("Face value must be between 1 and 6".to_string());
                 }
                 if dice_count == 0 {
@@ -137,16 +477,17 @@ pub fn take_action(game: &Game, action: &PlayerAction) -> Result<Game, String> {
                 }
                 
                 // Check if this bet is higher than the previous bet
-                if let Some(last_bet) = new_game.bets.last() {
-                    let (_, last_dice_count, last_face_value) = last_bet;
-                    if dice_count < *last_dice_count || 
-                       (dice_count == *last_dice_count && face_value <= *last_face_value) {
-                        return Err("New bet must be higher than the previous bet".to_string());
-                    }
+                if !is_raise(game, (dice_count, face_value)) {
+                    return Err("New bet must be higher than the previous bet".to_string());
                 }
                 
                 // Add the bet to the betting history
-                new_game.bets.push((game.current_player as u8, dice_count, face_value));
+                new_game.bets.push((game.current_player, dice_count, face_value));
+                new_game.events.push(GameEvent::Bet {
+                    player: game.current_player,
+                    dice_count,
+                    face_value,
+                });
                 // Advance to the next player, skipping those with 0 dice
                 loop {
                     new_game.current_player = (new_game.current_player + 1) % new_game.player_count;
@@ -647,4 +988,363 @@ mod tests {
         assert_eq!(new_game.current_player_dice_count[2], 0);
         assert_eq!(new_game.current_player_dice_count[3], 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_call_keeps_previous_round_hands() {
+        let mut game = create_test_game();
+        assert_eq!(game.last_round, None);
+
+        game.player_dice[0] = [1, 2, 3, 4, 5];
+        game.player_dice[1] = [6, 6, 6, 6, 6];
+        let dice_before_call = game.player_dice;
+
+        // Player 0 bets
+        let bet = PlayerAction {
+            action: Action::Bet,
+            bet: Some((3, 6)),
+        };
+        game = take_action(&game, &bet).unwrap();
+
+        // Player 1 calls
+        let call = PlayerAction {
+            action: Action::Call,
+            bet: None,
+        };
+        let new_game = take_action(&game, &call).unwrap();
+
+        // The hands that settled the bet are kept, with the dice counts from before the loss
+        let hands = new_game.last_round.unwrap().hands;
+        assert_eq!(hands.player_dice, dice_before_call);
+        assert_eq!(hands.dice_count[0], 5);
+        assert_eq!(hands.dice_count[1], 5);
+    }
+
+    #[test]
+    fn test_call_records_round_resolution() {
+        let mut game = create_test_game();
+
+        // Only two dice show 4
+        game.player_dice[0] = [4, 1, 1, 1, 1];
+        game.player_dice[1] = [2, 2, 2, 2, 2];
+        game.player_dice[2] = [4, 3, 3, 3, 3];
+        game.player_dice[3] = [6, 6, 6, 6, 6];
+
+        // Player 0 bets, player 1 raises to three 4s, player 2 calls
+        let bet = PlayerAction {
+            action: Action::Bet,
+            bet: Some((2, 4)),
+        };
+        game = take_action(&game, &bet).unwrap();
+        let raise = PlayerAction {
+            action: Action::Bet,
+            bet: Some((3, 4)),
+        };
+        game = take_action(&game, &raise).unwrap();
+        let call = PlayerAction {
+            action: Action::Call,
+            bet: None,
+        };
+        let new_game = take_action(&game, &call).unwrap();
+
+        let resolution = new_game.last_round.unwrap();
+        assert_eq!(resolution.bet, (1, 3, 4));
+        assert_eq!(resolution.face_value(), 4);
+        assert_eq!(resolution.caller, 2);
+        assert_eq!(resolution.actual_count, 2);
+        assert_eq!(resolution.losing_player, 1); // The bet was a bluff
+    }
+
+    #[test]
+    fn test_human_count_only_counts_seated_players() {
+        let mut game = create_test_game();
+        assert_eq!(game.human_count(), 1); // Player 0 is human by default
+
+        game.is_human[2] = true;
+        game.is_human[6] = true; // Seat beyond player_count is ignored
+        assert_eq!(game.human_count(), 2);
+    }
+
+    #[test]
+    fn test_call_reveals_without_rerolling() {
+        let mut game = create_test_game();
+        game.player_dice[0] = [1, 2, 3, 4, 5];
+        let dice_before_call = game.player_dice;
+
+        let bet = PlayerAction {
+            action: Action::Bet,
+            bet: Some((3, 5)),
+        };
+        game = take_action(&game, &bet).unwrap();
+        let call = PlayerAction {
+            action: Action::Call,
+            bet: None,
+        };
+        let new_game = take_action(&game, &call).unwrap();
+
+        // The dice that settled the bet are still on the table
+        assert_eq!(new_game.phase, Phase::Revealing);
+        assert_eq!(new_game.player_dice, dice_before_call);
+    }
+
+    #[test]
+    fn test_action_outside_bidding_returns_error() {
+        let mut game = create_test_game();
+        game.phase = Phase::Revealing;
+
+        let bet = PlayerAction {
+            action: Action::Bet,
+            bet: Some((3, 5)),
+        };
+        let result = take_action(&game, &bet);
+        assert_eq!(result.unwrap_err(), "Actions can only be taken while bidding");
+    }
+
+    #[test]
+    fn test_advance_phase_runs_round_lifecycle() {
+        let mut game = create_test_game();
+        assert_eq!(advance_phase(&game).unwrap_err(), "The round is still being bid on");
+
+        game.phase = Phase::Revealing;
+        game = advance_phase(&game).unwrap();
+        assert_eq!(game.phase, Phase::Dealing);
+        assert_eq!(game.player_dice[0], [0; DICE_PER_PLAYER]); // Nothing rolled yet
+
+        game = advance_phase(&game).unwrap();
+        assert_eq!(game.phase, Phase::Bidding);
+        assert!(game.player_dice[0].iter().all(|face| (1..=6).contains(face)));
+    }
+
+    #[test]
+    fn test_legal_raises_without_bets_covers_every_bet() {
+        let game = create_test_game();
+        let raises = legal_raises(&game);
+        assert_eq!(game.total_dice(), 20);
+        assert_eq!(raises.len(), 20 * 6);
+        assert_eq!(raises.first(), Some(&(1, 1)));
+        assert_eq!(raises.last(), Some(&(20, 6)));
+    }
+
+    #[test]
+    fn test_legal_raises_start_above_last_bet() {
+        let mut game = create_test_game();
+        let bet = PlayerAction {
+            action: Action::Bet,
+            bet: Some((3, 5)),
+        };
+        game = take_action(&game, &bet).unwrap();
+
+        let raises = legal_raises(&game);
+        assert_eq!(&raises[..3], &[(3, 6), (4, 1), (4, 2)]);
+        assert_eq!(minimum_raise(&game), Some((3, 6)));
+        assert!(!is_legal_bet(&game, (3, 5)));
+        assert!(is_legal_bet(&game, (4, 1)));
+    }
+
+    #[test]
+    fn test_no_minimum_raise_above_all_dice() {
+        let mut game = create_test_game();
+        game.bets.push((0, 20, 6));
+        assert!(legal_raises(&game).is_empty());
+        assert_eq!(minimum_raise(&game), None);
+    }
+
+    #[test]
+    fn test_with_players_sets_up_seats_and_starting_dice() {
+        let rules = Rules {
+            ones_wild: false,
+            starting_dice: 3,
+        };
+        let game = Game::with_players(6, rules);
+        assert_eq!(game.player_count, 6);
+        assert_eq!(game.player_names[5], "Player 6");
+        assert_eq!(&game.current_player_dice_count[..6], &[3; 6]);
+        assert_eq!(game.current_player_dice_count[6], 0); // Empty seat
+        assert_eq!(game.total_dice(), 18);
+    }
+
+    #[test]
+    fn test_with_players_clamps_setup() {
+        let rules = Rules {
+            ones_wild: false,
+            starting_dice: 9,
+        };
+        let game = Game::with_players(12, rules);
+        assert_eq!(game.player_count, 8);
+        assert_eq!(game.rules.starting_dice, 5);
+    }
+
+    #[test]
+    fn test_wild_ones_count_towards_bet() {
+        let rules = Rules {
+            ones_wild: true,
+            ..Rules::default()
+        };
+        let mut game = Game::with_players(2, rules);
+        game.player_dice[0] = [5, 1, 2, 2, 2];
+        game.player_dice[1] = [1, 5, 3, 3, 3];
+
+        // Two 5s plus two wild 1s make four
+        let bet = PlayerAction {
+            action: Action::Bet,
+            bet: Some((4, 5)),
+        };
+        game = take_action(&game, &bet).unwrap();
+        let call = PlayerAction {
+            action: Action::Call,
+            bet: None,
+        };
+        let new_game = take_action(&game, &call).unwrap();
+
+        let resolution = new_game.last_round.unwrap();
+        assert_eq!(resolution.actual_count, 4);
+        assert_eq!(resolution.wild_count, 2);
+        assert_eq!(resolution.losing_player, 1); // The bet held, so the caller loses
+    }
+
+    #[test]
+    fn test_ones_are_not_wild_by_default() {
+        let mut game = Game::with_players(2, Rules::default());
+        game.player_dice[0] = [5, 1, 2, 2, 2];
+        game.player_dice[1] = [1, 5, 3, 3, 3];
+
+        let bet = PlayerAction {
+            action: Action::Bet,
+            bet: Some((4, 5)),
+        };
+        game = take_action(&game, &bet).unwrap();
+        let call = PlayerAction {
+            action: Action::Call,
+            bet: None,
+        };
+        let new_game = take_action(&game, &call).unwrap();
+
+        let resolution = new_game.last_round.unwrap();
+        assert_eq!(resolution.actual_count, 2);
+        assert_eq!(resolution.wild_count, 0);
+        assert_eq!(resolution.losing_player, 0);
+    }
+
+    #[test]
+    fn test_events_record_the_game_in_order() {
+        let mut game = Game::with_players(2, Rules { ones_wild: false, starting_dice: 1 });
+        game.player_dice[0] = [3, 0, 0, 0, 0];
+        game.player_dice[1] = [4, 0, 0, 0, 0];
+
+        let bet = PlayerAction {
+            action: Action::Bet,
+            bet: Some((2, 3)),
+        };
+        game = take_action(&game, &bet).unwrap();
+        let call = PlayerAction {
+            action: Action::Call,
+            bet: None,
+        };
+        game = take_action(&game, &call).unwrap();
+
+        assert_eq!(game.events.len(), 4);
+        assert_eq!(game.events[0], GameEvent::Bet { player: 0, dice_count: 2, face_value: 3 });
+        assert!(matches!(&game.events[1], GameEvent::Call(resolution) if resolution.losing_player == 0));
+        assert_eq!(game.events[2], GameEvent::Eliminated(Elimination { player: 0, round: 1 }));
+        assert_eq!(game.events[3], GameEvent::Won { player: 1 });
+        assert_eq!(game.finishing_order(), vec![1, 0]);
+    }
+
+    #[test]
+    fn test_next_round_skips_eliminated_starter() {
+        let mut game = Game::with_players(3, Rules { ones_wild: false, starting_dice: 1 });
+        game.player_dice[0] = [2, 0, 0, 0, 0];
+        game.player_dice[1] = [3, 0, 0, 0, 0];
+        game.player_dice[2] = [3, 0, 0, 0, 0];
+
+        // Player 0 bluffs, player 1 calls, and player 0 is out before starting the next round
+        let bet = PlayerAction {
+            action: Action::Bet,
+            bet: Some((2, 6)),
+        };
+        game = take_action(&game, &bet).unwrap();
+        game.round_starter = 2; // Player 0 would be next to start
+        let call = PlayerAction {
+            action: Action::Call,
+            bet: None,
+        };
+        game = take_action(&game, &call).unwrap();
+
+        assert_eq!(game.current_player_dice_count[0], 0);
+        assert_eq!(game.round_starter, 1);
+        assert_eq!(game.current_player, 1);
+    }
+
+    #[test]
+    fn test_eliminations_record_order_and_round() {
+        let mut game = Game::with_players(3, Rules { ones_wild: false, starting_dice: 1 });
+        let bet = PlayerAction {
+            action: Action::Bet,
+            bet: Some((3, 6)),
+        };
+        let call = PlayerAction {
+            action: Action::Call,
+            bet: None,
+        };
+
+        // Round 1: player 0 overbids and is called out
+        game.player_dice[..3].copy_from_slice(&[[2; 5], [3; 5], [4; 5]]);
+        game = take_action(&take_action(&game, &bet).unwrap(), &call).unwrap();
+        assert_eq!(game.round, 2);
+        game = advance_phase(&advance_phase(&game).unwrap()).unwrap();
+
+        // Round 2: player 1 starts, overbids and is called out by player 2
+        game.player_dice[..3].copy_from_slice(&[[2; 5], [3; 5], [4; 5]]);
+        game = take_action(&take_action(&game, &bet).unwrap(), &call).unwrap();
+
+        assert_eq!(game.winner, Some(2));
+        assert_eq!(game.round, 2); // The round count stops with the game
+        assert_eq!(game.finishing_order(), vec![2, 1, 0]);
+        assert_eq!(game.elimination_round(0), Some(1));
+        assert_eq!(game.elimination_round(1), Some(2));
+        assert_eq!(game.elimination_round(2), None);
+    }
+
+    #[test]
+    fn test_timeout_defaults() {
+        let mut game = create_test_game();
+        game = take_action(&game, &PlayerAction { action: Action::Bet, bet: Some((2, 6)) }).unwrap();
+
+        let raised = apply_timeout(&game, TimeoutAction::MinimumRaise).unwrap();
+        assert_eq!(raised.bets.last(), Some(&(1, 3, 1)));
+
+        let called = apply_timeout(&game, TimeoutAction::Call).unwrap();
+        assert_eq!(called.phase, Phase::Revealing);
+        assert_eq!(called.last_round.as_ref().map(|round| round.caller), Some(1));
+
+        let timed_out = apply_timeout(&game, TimeoutAction::LoseDie).unwrap();
+        assert_eq!(timed_out.current_player_dice_count[1], 4);
+        assert_eq!(timed_out.phase, Phase::Dealing);
+        assert!(timed_out.bets.is_empty());
+        assert_eq!(timed_out.round, 2);
+        assert!(matches!(timed_out.events.last(), Some(GameEvent::TimedOut { player: 1, .. })));
+    }
+
+    #[test]
+    fn test_previous_round_hands_follow_calls_and_timeouts() {
+        let mut game = create_test_game();
+        assert_eq!(game.previous_round_hands(), None);
+        game = take_action(&game, &PlayerAction { action: Action::Bet, bet: Some((2, 6)) }).unwrap();
+        let called = take_action(&game, &PlayerAction { action: Action::Call, bet: None }).unwrap();
+        assert_eq!(called.previous_round_hands(), called.last_round.as_ref().map(|round| &round.hands));
+
+        // A timeout ends the next round without a call, so the called hands are out of date
+        let mut next = advance_phase(&advance_phase(&called).unwrap()).unwrap();
+        next.player_dice[0] = [4, 4, 4, 4, 0];
+        let dealt = next.player_dice;
+        let timed_out = apply_timeout(&next, TimeoutAction::LoseDie).unwrap();
+        assert_eq!(timed_out.previous_round_hands().map(|hands| hands.player_dice), Some(dealt));
+        assert_ne!(timed_out.previous_round_hands(), timed_out.last_round.as_ref().map(|round| &round.hands));
+    }
+
+    #[test]
+    fn test_opening_call_timeout_bets_instead() {
+        let game = create_test_game();
+        let opened = apply_timeout(&game, TimeoutAction::Call).unwrap();
+        assert_eq!(opened.bets, vec![(0, 1, 1)]);
+    }
+}
@@ -0,0 +1,208 @@
+// The bidding history beside the table: every round's bids pieced together from the game's
+// events, and which lines of it the panel shows
+
+use crate::game::{GameEvent, RoundResolution};
+
+/// How a finished round ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoundOutcome {
+    Called(RoundResolution),
+    TimedOut(u8), // The player whose time ran out, who lost a die
+}
+
+impl RoundOutcome {
+    pub fn losing_player(&self) -> u8 {
+        match self {
+            RoundOutcome::Called(resolution) => resolution.losing_player,
+            RoundOutcome::TimedOut(player) => *player,
+        }
+    }
+}
+
+/// One round's bids, in the order they were made
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundRecord {
+    pub number: u32, // Counting from 1
+    pub bids: Vec<(u8, u8, u8)>, // (player_index, dice_count, face_value)
+    pub outcome: Option<RoundOutcome>, // None while the round is still being bid on
+    pub eliminated: Vec<u8>, // Players who lost their last die in this round
+}
+
+impl RoundRecord {
+    fn new(number: u32) -> Self {
+        Self {
+            number,
+            bids: Vec::new(),
+            outcome: None,
+            eliminated: Vec::new(),
+        }
+    }
+}
+
+/// Every round so far, oldest first. The last one is the round being bid on, unless the
+/// game is over.
+pub fn round_history(events: &[GameEvent]) -> Vec<RoundRecord> {
+    let mut rounds: Vec<RoundRecord> = Vec::new();
+    let mut current = RoundRecord::new(1);
+    let mut game_over = false;
+
+    for event in events {
+        let outcome = match event {
+            GameEvent::Bet { player, dice_count, face_value } => {
+                current.bids.push((*player, *dice_count, *face_value));
+                continue;
+            }
+            GameEvent::Call(resolution) => RoundOutcome::Called(resolution.clone()),
+            GameEvent::TimedOut { player, .. } => RoundOutcome::TimedOut(*player),
+            GameEvent::Eliminated(elimination) => {
+                if let Some(round) = rounds.last_mut() {
+                    round.eliminated.push(elimination.player);
+                }
+                continue;
+            }
+            GameEvent::Won { .. } => {
+                game_over = true;
+                continue;
+            }
+        };
+        let next = RoundRecord::new(current.number + 1);
+        current.outcome = Some(outcome);
+        rounds.push(std::mem::replace(&mut current, next));
+    }
+
+    if !game_over {
+        rounds.push(current);
+    }
+    rounds
+}
+
+/// A line of the history panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryLine {
+    Round { index: usize, expanded: bool }, // A round's heading; finished rounds open and close when clicked
+    Bid { round: usize, bid: usize },
+    Outcome(usize), // How a finished round ended, under its bids
+    NoBids,         // The round being bid on has no bids yet
+}
+
+/// The panel's lines, oldest first. Finished rounds show only their heading unless their
+/// number is in `expanded`; the round being bid on always shows its bids.
+pub fn history_lines(rounds: &[RoundRecord], expanded: &[u32]) -> Vec<HistoryLine> {
+    let mut lines = Vec::new();
+    for (index, round) in rounds.iter().enumerate() {
+        let finished = round.outcome.is_some();
+        let open = !finished || expanded.contains(&round.number);
+        lines.push(HistoryLine::Round { index, expanded: open });
+        if !open {
+            continue;
+        }
+        lines.extend((0..round.bids.len()).map(|bid| HistoryLine::Bid { round: index, bid }));
+        if finished {
+            lines.push(HistoryLine::Outcome(index));
+        } else if round.bids.is_empty() {
+            lines.push(HistoryLine::NoBids);
+        }
+    }
+    lines
+}
+
+/// Which lines fit in `visible` rows when scrolled `scroll` lines up from the newest,
+/// and the scroll clamped to what there is to see
+pub fn visible_lines(line_count: usize, visible: usize, scroll: usize) -> (std::ops::Range<usize>, usize) {
+    let scroll = scroll.min(line_count.saturating_sub(visible));
+    let end = line_count - scroll;
+    (end.saturating_sub(visible)..end, scroll)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::{advance_phase, take_action, Action, Game, Phase, PlayerAction, Rules};
+
+    fn bet(dice_count: u8, face_value: u8) -> PlayerAction {
+        PlayerAction {
+            action: Action::Bet,
+            bet: Some((dice_count, face_value)),
+        }
+    }
+
+    fn call() -> PlayerAction {
+        PlayerAction {
+            action: Action::Call,
+            bet: None,
+        }
+    }
+
+    // Plays the actions, dealing the next round after each call
+    fn play(mut game: Game, actions: &[PlayerAction]) -> Game {
+        for action in actions {
+            while game.phase != Phase::Bidding {
+                game = advance_phase(&game).unwrap();
+            }
+            game = take_action(&game, action).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn test_rounds_keep_their_bids_after_a_call() {
+        let game = play(Game::with_players(3, Rules::default()), &[bet(1, 2), bet(2, 3), call(), bet(1, 5)]);
+
+        let rounds = round_history(&game.events);
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(rounds[0].bids, [(0, 1, 2), (1, 2, 3)]);
+        assert!(matches!(&rounds[0].outcome, Some(RoundOutcome::Called(resolution)) if resolution.caller == 2));
+        assert_eq!(rounds[1].number, 2);
+        assert_eq!(rounds[1].bids.len(), 1);
+        assert_eq!(rounds[1].outcome, None);
+        // The engine only keeps the current round's bids
+        assert_eq!(game.bets.len(), 1);
+    }
+
+    #[test]
+    fn test_eliminations_belong_to_the_round_they_happened_in() {
+        let mut game = Game::with_players(2, Rules { ones_wild: false, starting_dice: 1 });
+        game.player_dice[0] = [2, 0, 0, 0, 0];
+        game.player_dice[1] = [3, 0, 0, 0, 0];
+        let game = play(game, &[bet(2, 6), call()]);
+
+        let rounds = round_history(&game.events);
+        assert_eq!(rounds.len(), 1, "no round is being bid on once the game is won");
+        assert_eq!(rounds[0].eliminated, [0]);
+    }
+
+    #[test]
+    fn test_finished_rounds_open_when_expanded() {
+        let game = play(Game::with_players(2, Rules::default()), &[bet(1, 2), bet(1, 3), call()]);
+        let rounds = round_history(&game.events);
+
+        assert_eq!(
+            history_lines(&rounds, &[]),
+            [
+                HistoryLine::Round { index: 0, expanded: false },
+                HistoryLine::Round { index: 1, expanded: true },
+                HistoryLine::NoBids,
+            ]
+        );
+        assert_eq!(
+            history_lines(&rounds, &[1]),
+            [
+                HistoryLine::Round { index: 0, expanded: true },
+                HistoryLine::Bid { round: 0, bid: 0 },
+                HistoryLine::Bid { round: 0, bid: 1 },
+                HistoryLine::Outcome(0),
+                HistoryLine::Round { index: 1, expanded: true },
+                HistoryLine::NoBids,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scrolling_stops_at_the_oldest_line() {
+        assert_eq!(visible_lines(20, 5, 0), (15..20, 0));
+        assert_eq!(visible_lines(20, 5, 3), (12..17, 3));
+        assert_eq!(visible_lines(20, 5, 50), (0..5, 15));
+        assert_eq!(visible_lines(3, 5, 2), (0..3, 0));
+    }
+}
@@ -0,0 +1,265 @@
+// Translations of the text on every screen. Messages are read from the catalogs in locales/,
+// one file per language, so a translation can be fixed without touching the code.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+
+/// Catalogs are read from `locales/<name>.txt`, falling back to the copies built into the game
+pub const LOCALES_DIR: &str = "locales";
+
+const ENGLISH: &str = include_str!("../locales/en.txt");
+const SPANISH: &str = include_str!("../locales/es.txt");
+const GERMAN: &str = include_str!("../locales/de.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Spanish,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::English, Language::Spanish, Language::German];
+
+    /// The language code, which is also the name of its catalog file
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Spanish => "es",
+            Language::German => "de",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|language| language.name() == name)
+    }
+
+    /// Each language is listed in itself, so it can be found whatever language is showing
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+            Language::German => "Deutsch",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Language::English => Language::Spanish,
+            Language::Spanish => Language::German,
+            Language::German => Language::English,
+        }
+    }
+
+    fn bundled_catalog(self) -> &'static str {
+        match self {
+            Language::English => ENGLISH,
+            Language::Spanish => SPANISH,
+            Language::German => GERMAN,
+        }
+    }
+
+    /// Which form of a counted message goes with `count`. English, Spanish and German
+    /// all have one form for exactly one and another for everything else.
+    fn plural_form(self, count: u32) -> &'static str {
+        match (self, count) {
+            (Language::English | Language::Spanish | Language::German, 1) => "one",
+            _ => "other",
+        }
+    }
+}
+
+/// The messages for one language, with English filling any gaps in a translation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Catalog {
+    language: Language,
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// The catalog built into the game
+    pub fn bundled(language: Language) -> Self {
+        Self::with_messages(language, language.bundled_catalog()).unwrap_or_else(|_| Self {
+            language,
+            messages: parse_catalog(ENGLISH).unwrap_or_default(),
+        })
+    }
+
+    /// Reads a language's catalog from `dir`, or uses the built-in one if there is no file for it
+    pub fn load(language: Language, dir: &Path) -> Result<Self, String> {
+        let path = dir.join(format!("{}.txt", language.name()));
+        if !path.is_file() {
+            return Ok(Self::bundled(language));
+        }
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        Self::with_messages(language, &text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    fn with_messages(language: Language, text: &str) -> Result<Self, String> {
+        let mut messages = parse_catalog(ENGLISH)?;
+        messages.extend(parse_catalog(text)?);
+        Ok(Self { language, messages })
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// The message for `key`, or the key itself if no catalog has it
+    pub fn message<'a>(&'a self, key: &'a str) -> &'a str {
+        self.messages.get(key).map_or(key, String::as_str)
+    }
+
+    /// The message for `key` with each `{name}` replaced by its argument
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.message(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        text
+    }
+
+    /// The form of `key` that goes with `count`, such as `dice.one` or `dice.other`, with
+    /// `{count}` and the other arguments filled in
+    pub fn format_count(&self, key: &str, count: u32, args: &[(&str, &dyn Display)]) -> String {
+        let key = format!("{}.{}", key, self.language.plural_form(count));
+        let mut args = args.to_vec();
+        args.push(("count", &count));
+        self.format(&key, &args)
+    }
+}
+
+/// Reads `key = message` lines. Blank lines and lines starting with `#` are ignored.
+pub fn parse_catalog(text: &str) -> Result<HashMap<String, String>, String> {
+    let mut messages = HashMap::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: String| format!("Line {}: {}", line_number + 1, message);
+
+        let (key, message) = line.split_once('=').ok_or(error("expected `key = message`".to_string()))?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(error("expected a key before `=`".to_string()));
+        }
+        if messages.insert(key.to_string(), message.trim().to_string()).is_some() {
+            return Err(error(format!("`{}` is already defined", key)));
+        }
+    }
+    Ok(messages)
+}
+
+// Every screen draws text, so the catalog in use is kept here rather than passed to each
+// of them. Only the main thread draws; tests and other threads see English.
+thread_local! {
+    static ACTIVE: RefCell<Catalog> = RefCell::new(Catalog::bundled(Language::English));
+}
+
+/// Switches the text on every screen to `catalog`
+pub fn use_catalog(catalog: Catalog) {
+    ACTIVE.with(|active| *active.borrow_mut() = catalog);
+}
+
+/// The language the screens are drawn in
+pub fn language() -> Language {
+    ACTIVE.with(|active| active.borrow().language())
+}
+
+/// The message for `key` in the current language
+pub fn tr(key: &str) -> String {
+    ACTIVE.with(|active| active.borrow().message(key).to_string())
+}
+
+/// The message for `key` in the current language, with its arguments filled in
+pub fn tr_args(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    ACTIVE.with(|active| active.borrow().format(key, args))
+}
+
+/// The form of `key` for `count` in the current language, with its arguments filled in
+pub fn tr_count(key: &str, count: u32, args: &[(&str, &dyn Display)]) -> String {
+    ACTIVE.with(|active| active.borrow().format_count(key, count, args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeSet;
+
+    // The `{name}` placeholders in a message
+    fn placeholders(message: &str) -> BTreeSet<&str> {
+        message
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn test_language_names_round_trip() {
+        for language in Language::ALL {
+            assert_eq!(Language::from_name(language.name()), Some(language));
+        }
+        assert_eq!(Language::from_name("fr"), None);
+    }
+
+    #[test]
+    fn test_every_catalog_translates_every_message() {
+        let english = parse_catalog(ENGLISH).unwrap();
+        for language in Language::ALL {
+            let messages = parse_catalog(language.bundled_catalog()).unwrap();
+            for (key, message) in &english {
+                let translation = messages.get(key).unwrap_or_else(|| panic!("{:?} is missing `{}`", language, key));
+                assert_eq!(placeholders(translation), placeholders(message), "{:?} `{}`", language, key);
+            }
+            let extra: Vec<&String> = messages.keys().filter(|key| !english.contains_key(*key)).collect();
+            assert!(extra.is_empty(), "{:?} has unknown keys {:?}", language, extra);
+        }
+    }
+
+    #[test]
+    fn test_counted_messages_pick_the_plural_form() {
+        let english = Catalog::bundled(Language::English);
+        let bet = |count| english.format_count("game.history_bet", count, &[("player", &"Ana"), ("face", &5)]);
+        assert_eq!(bet(1), "Ana: 1 die showing 5");
+        assert_eq!(bet(3), "Ana: 3 dice showing 5");
+        assert_eq!(bet(0), "Ana: 0 dice showing 5");
+
+        let spanish = Catalog::bundled(Language::Spanish);
+        assert_eq!(spanish.format_count("game.dice_count", 1, &[]), "1 dado");
+        assert_eq!(spanish.format_count("game.dice_count", 2, &[]), "2 dados");
+    }
+
+    #[test]
+    fn test_missing_messages_fall_back_to_english_then_the_key() {
+        let catalog = Catalog::with_messages(Language::German, "menu.quit = Beenden").unwrap();
+        assert_eq!(catalog.message("menu.quit"), "Beenden");
+        assert_eq!(catalog.message("menu.tutorial"), "Tutorial");
+        assert_eq!(catalog.message("no.such.key"), "no.such.key");
+        assert_eq!(catalog.format("handoff.pass_to", &[("name", &"Ana")]), "Pass to Ana");
+    }
+
+    #[test]
+    fn test_bad_catalog_lines_are_reported() {
+        assert_eq!(parse_catalog("# Comment\n\nmenu.quit Quit"), Err("Line 3: expected `key = message`".to_string()));
+        assert_eq!(parse_catalog("a = 1\na = 2"), Err("Line 2: `a` is already defined".to_string()));
+        assert!(parse_catalog("= Quit").is_err());
+    }
+
+    #[test]
+    fn test_catalogs_load_from_files_with_the_bundled_ones_as_fallback() {
+        let dir = std::env::temp_dir().join(format!("rusty-dice-locales-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("de.txt"), "menu.quit = Aufhören\n").unwrap();
+
+        assert_eq!(Catalog::load(Language::German, &dir).unwrap().message("menu.quit"), "Aufhören");
+        assert_eq!(Catalog::load(Language::Spanish, &dir).unwrap(), Catalog::bundled(Language::Spanish));
+        std::fs::write(dir.join("de.txt"), "menu.quit\n").unwrap();
+        assert!(Catalog::load(Language::German, &dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
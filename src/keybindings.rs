@@ -0,0 +1,260 @@
+// Keyboard controls for Rusty Dice, rebindable through a settings file
+
+use macroquad::prelude::*;
+
+pub const KEY_BINDINGS_FILE: &str = "keybindings.cfg";
+
+/// Something the player can do from the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    IncreaseQuantity,
+    DecreaseQuantity,
+    NextFace,
+    PreviousFace,
+    SelectFace(u8),
+    MinimumRaise,
+    Activate, // Presses the focused control
+    Call,
+    NextFocus,
+    PreviousFocus,
+    ToggleHints,
+    Undo,
+    FastForward, // Plays the AI moves without pausing until a human is up or the round ends
+    SkipAnimation,
+}
+
+impl Command {
+    fn from_name(name: &str) -> Option<Self> {
+        let command = match name {
+            "increase_quantity" => Command::IncreaseQuantity,
+            "decrease_quantity" => Command::DecreaseQuantity,
+            "next_face" => Command::NextFace,
+            "previous_face" => Command::PreviousFace,
+            "minimum_raise" => Command::MinimumRaise,
+            "activate" => Command::Activate,
+            "call" => Command::Call,
+            "next_focus" => Command::NextFocus,
+            "previous_focus" => Command::PreviousFocus,
+            "toggle_hints" => Command::ToggleHints,
+            "undo" => Command::Undo,
+            "fast_forward" => Command::FastForward,
+            "skip_animation" => Command::SkipAnimation,
+            _ => {
+                let face_value: u8 = name.strip_prefix("select_face_")?.parse().ok()?;
+                if !(1..=6).contains(&face_value) {
+                    return None;
+                }
+                Command::SelectFace(face_value)
+            }
+        };
+        Some(command)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBindings {
+    pub bindings: Vec<(Command, Vec<KeyCode>)>,
+}
+
+impl KeyBindings {
+    pub fn new() -> Self {
+        Self {
+            bindings: vec![
+                (Command::IncreaseQuantity, vec![KeyCode::Up, KeyCode::Equal, KeyCode::KpAdd]),
+                (Command::DecreaseQuantity, vec![KeyCode::Down, KeyCode::Minus, KeyCode::KpSubtract]),
+                (Command::NextFace, vec![KeyCode::Right]),
+                (Command::PreviousFace, vec![KeyCode::Left]),
+                (Command::SelectFace(1), vec![KeyCode::Key1]),
+                (Command::SelectFace(2), vec![KeyCode::Key2]),
+                (Command::SelectFace(3), vec![KeyCode::Key3]),
+                (Command::SelectFace(4), vec![KeyCode::Key4]),
+                (Command::SelectFace(5), vec![KeyCode::Key5]),
+                (Command::SelectFace(6), vec![KeyCode::Key6]),
+                (Command::MinimumRaise, vec![KeyCode::M]),
+                (Command::Activate, vec![KeyCode::Enter, KeyCode::KpEnter]),
+                (Command::Call, vec![KeyCode::C]),
+                (Command::NextFocus, vec![KeyCode::Tab]),
+                (Command::PreviousFocus, vec![KeyCode::Backspace]),
+                (Command::ToggleHints, vec![KeyCode::H]),
+                (Command::Undo, vec![KeyCode::U]),
+                (Command::FastForward, vec![KeyCode::F]),
+                (Command::SkipAnimation, vec![KeyCode::Space]),
+            ],
+        }
+    }
+
+    /// Loads bindings from the settings file, falling back to the defaults if it is missing
+    pub fn load(path: &str) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(format!("Could not read {}: {}", path, e)),
+        }
+    }
+
+    /// Parses `command = Key, Key` lines over the defaults. Blank lines and lines
+    /// starting with `#` are ignored.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut key_bindings = Self::new();
+
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((name, keys)) = line.split_once('=') else {
+                return Err(format!("Line {}: expected `command = Key`", line_number + 1));
+            };
+            let Some(command) = Command::from_name(name.trim()) else {
+                return Err(format!("Line {}: unknown command `{}`", line_number + 1, name.trim()));
+            };
+
+            let mut key_codes = Vec::new();
+            for key in keys.split(',').map(str::trim).filter(|key| !key.is_empty()) {
+                match key_code_from_name(key) {
+                    Some(key_code) => key_codes.push(key_code),
+                    None => return Err(format!("Line {}: unknown key `{}`", line_number + 1, key)),
+                }
+            }
+
+            key_bindings.bind(command, key_codes);
+        }
+
+        Ok(key_bindings)
+    }
+
+    /// Replaces the keys bound to a command
+    pub fn bind(&mut self, command: Command, key_codes: Vec<KeyCode>) {
+        match self.bindings.iter_mut().find(|(bound, _)| *bound == command) {
+            Some((_, keys)) => *keys = key_codes,
+            None => self.bindings.push((command, key_codes)),
+        }
+    }
+
+    /// Commands whose keys were pressed this frame
+    pub fn pressed_commands(&self) -> Vec<Command> {
+        self.bindings
+            .iter()
+            .filter(|(_, keys)| keys.iter().any(|&key| is_key_pressed(key)))
+            .map(|(command, _)| *command)
+            .collect()
+    }
+}
+
+fn key_code_from_name(name: &str) -> Option<KeyCode> {
+    let key_code = match name {
+        "A" => KeyCode::A,
+        "B" => KeyCode::B,
+        "C" => KeyCode::C,
+        "D" => KeyCode::D,
+        "E" => KeyCode::E,
+        "F" => KeyCode::F,
+        "G" => KeyCode::G,
+        "H" => KeyCode::H,
+        "I" => KeyCode::I,
+        "J" => KeyCode::J,
+        "K" => KeyCode::K,
+        "L" => KeyCode::L,
+        "M" => KeyCode::M,
+        "N" => KeyCode::N,
+        "O" => KeyCode::O,
+        "P" => KeyCode::P,
+        "Q" => KeyCode::Q,
+        "R" => KeyCode::R,
+        "S" => KeyCode::S,
+        "T" => KeyCode::T,
+        "U" => KeyCode::U,
+        "V" => KeyCode::V,
+        "W" => KeyCode::W,
+        "X" => KeyCode::X,
+        "Y" => KeyCode::Y,
+        "Z" => KeyCode::Z,
+        "0" => KeyCode::Key0,
+        "1" => KeyCode::Key1,
+        "2" => KeyCode::Key2,
+        "3" => KeyCode::Key3,
+        "4" => KeyCode::Key4,
+        "5" => KeyCode::Key5,
+        "6" => KeyCode::Key6,
+        "7" => KeyCode::Key7,
+        "8" => KeyCode::Key8,
+        "9" => KeyCode::Key9,
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "Enter" => KeyCode::Enter,
+        "Tab" => KeyCode::Tab,
+        "Space" => KeyCode::Space,
+        "Backspace" => KeyCode::Backspace,
+        "Escape" => KeyCode::Escape,
+        "Minus" => KeyCode::Minus,
+        "Equal" => KeyCode::Equal,
+        "KpAdd" => KeyCode::KpAdd,
+        "KpSubtract" => KeyCode::KpSubtract,
+        "KpEnter" => KeyCode::KpEnter,
+        _ => return None,
+    };
+    Some(key_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys_for(key_bindings: &KeyBindings, command: Command) -> &[KeyCode] {
+        key_bindings
+            .bindings
+            .iter()
+            .find(|(bound, _)| *bound == command)
+            .map_or(&[], |(_, keys)| keys.as_slice())
+    }
+
+    #[test]
+    fn test_parse_empty_file_keeps_defaults() {
+        let key_bindings = KeyBindings::parse("").unwrap();
+        assert_eq!(key_bindings, KeyBindings::new());
+    }
+
+    #[test]
+    fn test_parse_rebinds_command() {
+        let contents = "# Bet with B, call with Q or X\nactivate = B\ncall = Q, X\n";
+        let key_bindings = KeyBindings::parse(contents).unwrap();
+        assert_eq!(keys_for(&key_bindings, Command::Activate), &[KeyCode::B]);
+        assert_eq!(keys_for(&key_bindings, Command::Call), &[KeyCode::Q, KeyCode::X]);
+        // Commands not in the file keep their default keys
+        assert_eq!(keys_for(&key_bindings, Command::NextFocus), &[KeyCode::Tab]);
+    }
+
+    #[test]
+    fn test_skip_animation_defaults_to_space_and_can_be_rebound() {
+        assert_eq!(keys_for(&KeyBindings::new(), Command::SkipAnimation), &[KeyCode::Space]);
+        let key_bindings = KeyBindings::parse("skip_animation = Escape").unwrap();
+        assert_eq!(keys_for(&key_bindings, Command::SkipAnimation), &[KeyCode::Escape]);
+    }
+
+    #[test]
+    fn test_parse_select_face() {
+        let key_bindings = KeyBindings::parse("select_face_6 = S").unwrap();
+        assert_eq!(keys_for(&key_bindings, Command::SelectFace(6)), &[KeyCode::S]);
+    }
+
+    #[test]
+    fn test_parse_unknown_command_returns_error() {
+        let result = KeyBindings::parse("select_face_7 = S");
+        assert_eq!(result.unwrap_err(), "Line 1: unknown command `select_face_7`");
+    }
+
+    #[test]
+    fn test_parse_unknown_key_returns_error() {
+        let result = KeyBindings::parse("call = C\nactivate = Hyper");
+        assert_eq!(result.unwrap_err(), "Line 2: unknown key `Hyper`");
+    }
+
+    #[test]
+    fn test_parse_missing_equals_returns_error() {
+        let result = KeyBindings::parse("call C");
+        assert_eq!(result.unwrap_err(), "Line 1: expected `command = Key`");
+    }
+}
@@ -0,0 +1,266 @@
+// Screen layout for the table view, recomputed from the window size every frame
+
+use macroquad::prelude::{Rect, Vec2};
+
+// The design resolution; everything is laid out at this size and scaled
+const BASE_WIDTH: f32 = 1200.0;
+const BASE_HEIGHT: f32 = 800.0;
+
+const HEADER_HEIGHT: f32 = 110.0;
+const CONTROLS_HEIGHT: f32 = 190.0;
+const HISTORY_PANEL_WIDTH: f32 = 220.0;
+const SIDE_PANEL_WIDTH: f32 = 240.0;
+const MARGIN: f32 = 20.0;
+
+const MAX_DICE_SIZE: f32 = 60.0;
+const MIN_DICE_SIZE: f32 = 8.0;
+const DICE_PER_SEAT: f32 = 5.0;
+
+// Bid picker: one row per dice count, one column per face
+pub const PICKER_VISIBLE_ROWS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub scale: f32,
+    pub header: Rect,        // Title and status lines
+    pub table: Rect,         // Oval table the seats sit around
+    pub history_panel: Rect, // Betting history, left of the table
+    pub side_panel: Rect,    // Round resolution, right of the table
+    pub controls: Rect,      // Buttons and bid picker along the bottom
+    pub seats: Vec<Vec2>,    // Centre of each player's seat, player 0 at the bottom
+    pub dice_size: f32,
+}
+
+impl Layout {
+    pub fn new(width: f32, height: f32, player_count: u8) -> Self {
+        let scale = (width / BASE_WIDTH).min(height / BASE_HEIGHT);
+        let margin = MARGIN * scale;
+
+        let header = Rect::new(0.0, 0.0, width, HEADER_HEIGHT * scale);
+        let controls_height = CONTROLS_HEIGHT * scale;
+        let controls = Rect::new(margin, height - controls_height, width - 2.0 * margin, controls_height - margin);
+
+        let middle_top = header.bottom() + margin;
+        let middle_height = controls.top() - margin - middle_top;
+        let history_panel = Rect::new(margin, middle_top, HISTORY_PANEL_WIDTH * scale, middle_height);
+        let side_panel_width = SIDE_PANEL_WIDTH * scale;
+        let side_panel = Rect::new(width - margin - side_panel_width, middle_top, side_panel_width, middle_height);
+        let table = Rect::new(
+            history_panel.right() + margin,
+            middle_top,
+            side_panel.left() - margin - (history_panel.right() + margin),
+            middle_height,
+        );
+
+        let mut layout = Self {
+            scale,
+            header,
+            table,
+            history_panel,
+            side_panel,
+            controls,
+            seats: Vec::new(),
+            dice_size: MAX_DICE_SIZE * scale,
+        };
+
+        // Shrink the dice until every seat fits around the table without touching its neighbours
+        loop {
+            layout.seats = layout.seat_centers(player_count);
+            if layout.dice_size <= MIN_DICE_SIZE || !layout.seats_overlap() {
+                break;
+            }
+            layout.dice_size = (layout.dice_size * 0.9).max(MIN_DICE_SIZE);
+        }
+
+        layout
+    }
+
+    /// Font size scaled from the design resolution
+    pub fn font(&self, base_size: f32) -> f32 {
+        base_size * self.scale
+    }
+
+    /// Distance between neighbouring dice centres in a hand
+    pub fn dice_spacing(&self) -> f32 {
+        self.dice_size * 4.0 / 3.0
+    }
+
+    /// Size of a seat: player name and dice count above a row of dice
+    pub fn seat_size(&self) -> Vec2 {
+        Vec2::new(
+            DICE_PER_SEAT * self.dice_spacing(),
+            self.font(24.0) + self.font(16.0) + self.dice_size * 1.5,
+        )
+    }
+
+    pub fn seat_rect(&self, player: usize) -> Rect {
+        let size = self.seat_size();
+        let center = self.seats[player];
+        Rect::new(center.x - size.x / 2.0, center.y - size.y / 2.0, size.x, size.y)
+    }
+
+    /// Centre of a die in a player's hand
+    pub fn dice_center(&self, player: usize, die: usize, dice_count: usize) -> Vec2 {
+        let seat = self.seat_rect(player);
+        let spacing = self.dice_spacing();
+        Vec2::new(
+            seat.center().x - (dice_count as f32 * spacing) / 2.0 + (die as f32 + 0.5) * spacing,
+            seat.bottom() - self.dice_size * 0.75,
+        )
+    }
+
+    pub fn call_button(&self) -> Rect {
+        self.control_button(0.0)
+    }
+
+    pub fn bet_button(&self) -> Rect {
+        self.control_button(150.0)
+    }
+
+    pub fn bid_picker(&self) -> Rect {
+        let row_height = self.picker_row_height();
+        let height = PICKER_VISIBLE_ROWS as f32 * row_height;
+        Rect::new(
+            self.controls.x + 300.0 * self.scale,
+            self.controls.bottom() - height,
+            50.0 * self.scale + 6.0 * (self.picker_icon_size() + 6.0 * self.scale),
+            height,
+        )
+    }
+
+    pub fn picker_row_height(&self) -> f32 {
+        36.0 * self.scale
+    }
+
+    pub fn picker_icon_size(&self) -> f32 {
+        30.0 * self.scale
+    }
+
+    /// Where the icon for a face sits in a visible bid picker row
+    pub fn picker_icon(&self, visible_row: usize, face_value: u8) -> Rect {
+        let picker = self.bid_picker();
+        let icon_size = self.picker_icon_size();
+        Rect::new(
+            picker.x + 50.0 * self.scale + (face_value - 1) as f32 * (icon_size + 6.0 * self.scale),
+            picker.y + visible_row as f32 * self.picker_row_height() + 3.0 * self.scale,
+            icon_size,
+            icon_size,
+        )
+    }
+
+    pub fn minimum_raise_button(&self) -> Rect {
+        let picker = self.bid_picker();
+        let button = self.control_button(0.0);
+        Rect::new(picker.right() + 20.0 * self.scale, button.y, button.w, button.h)
+    }
+
+    /// A 120x40 button on the bottom row of the controls, `offset` from their left edge
+    fn control_button(&self, offset: f32) -> Rect {
+        Rect::new(
+            self.controls.x + offset * self.scale,
+            self.controls.bottom() - 40.0 * self.scale,
+            120.0 * self.scale,
+            40.0 * self.scale,
+        )
+    }
+
+    /// Seats sit on an oval inside the table, going clockwise from the bottom
+    fn seat_centers(&self, player_count: u8) -> Vec<Vec2> {
+        let size = self.seat_size();
+        let center = self.table.center();
+        let radius_x = (self.table.w - size.x).max(0.0) / 2.0;
+        let radius_y = (self.table.h - size.y).max(0.0) / 2.0;
+
+        (0..player_count)
+            .map(|player| {
+                let angle = std::f32::consts::FRAC_PI_2
+                    + player as f32 * std::f32::consts::TAU / player_count as f32;
+                Vec2::new(center.x + radius_x * angle.cos(), center.y + radius_y * angle.sin())
+            })
+            .collect()
+    }
+
+    fn seats_overlap(&self) -> bool {
+        let seat_rects: Vec<Rect> = (0..self.seats.len()).map(|player| self.seat_rect(player)).collect();
+        seat_rects.iter().enumerate().any(|(i, seat)| {
+            let off_table = seat.w > self.table.w || seat.h > self.table.h;
+            off_table || seat_rects[i + 1..].iter().any(|other| other.overlaps(seat))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW_SIZES: [(f32, f32); 5] = [
+        (800.0, 600.0),
+        (1200.0, 800.0),
+        (1920.0, 1080.0),
+        (1000.0, 1000.0),
+        (3840.0, 2160.0),
+    ];
+
+    fn assert_apart(a: Rect, b: Rect, what: &str) {
+        assert!(!a.overlaps(&b), "{} overlap: {:?} and {:?}", what, a, b);
+    }
+
+    #[test]
+    fn test_panels_never_overlap() {
+        for (width, height) in WINDOW_SIZES {
+            let layout = Layout::new(width, height, 8);
+            assert_apart(layout.history_panel, layout.table, "History panel and table");
+            assert_apart(layout.side_panel, layout.table, "Side panel and table");
+            assert_apart(layout.table, layout.controls, "Table and controls");
+            assert_apart(layout.history_panel, layout.controls, "History panel and controls");
+            assert_apart(layout.header, layout.table, "Header and table");
+        }
+    }
+
+    #[test]
+    fn test_controls_fit_on_screen_without_overlapping() {
+        for (width, height) in WINDOW_SIZES {
+            let layout = Layout::new(width, height, 4);
+            let controls = [
+                layout.call_button(),
+                layout.bet_button(),
+                layout.bid_picker(),
+                layout.minimum_raise_button(),
+            ];
+            for (i, control) in controls.iter().enumerate() {
+                assert!(control.right() <= width && control.bottom() <= height, "Control {:?} is off screen", control);
+                for other in &controls[i + 1..] {
+                    assert_apart(*control, *other, "Controls");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_seats_fit_at_every_size_and_player_count() {
+        for (width, height) in WINDOW_SIZES {
+            for player_count in 2..=8 {
+                let layout = Layout::new(width, height, player_count);
+                assert_eq!(layout.seats.len(), player_count as usize);
+                assert!(!layout.seats_overlap(), "Seats overlap at {}x{} with {} players", width, height, player_count);
+                assert!(layout.dice_size > MIN_DICE_SIZE, "Dice at the minimum size at {}x{} with {} players", width, height, player_count);
+            }
+        }
+    }
+
+    #[test]
+    fn test_layout_scales_with_window() {
+        let small = Layout::new(1200.0, 800.0, 4);
+        let large = Layout::new(2400.0, 1600.0, 4);
+        assert_eq!(large.scale, 2.0 * small.scale);
+        assert_eq!(large.font(20.0), 2.0 * small.font(20.0));
+        assert!((large.dice_size - 2.0 * small.dice_size).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_player_zero_sits_at_the_bottom() {
+        let layout = Layout::new(1200.0, 800.0, 4);
+        assert!(layout.seats.iter().skip(1).all(|seat| seat.y <= layout.seats[0].y));
+        assert!((layout.seats[0].x - layout.table.center().x).abs() < 0.01);
+    }
+}
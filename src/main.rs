@@ -1,12 +1,50 @@
 use macroquad::prelude::*;
+use std::path::{Path, PathBuf};
 
 mod dice;
 mod game;
-use game::{Game, take_action, roll_all_dice};
+use game::Phase;
 mod render_game;
-use render_game::{render_game, handle_restart_click, RenderState};
+use render_game::{render_game, render_handoff_screen, handle_handoff_click, handle_restart_click, handle_main_menu_click, handle_stats_click, handle_save_game_click, Viewer};
 mod ai;
 use ai::ai_decide_action;
+mod animation;
+mod keybindings;
+mod layout;
+use keybindings::{KeyBindings, KEY_BINDINGS_FILE};
+mod settings;
+use settings::{Settings, SETTINGS_FILE};
+mod menu;
+use menu::{render_menu, Menu, MenuAction, MenuScreen};
+mod profiles;
+use profiles::{Profiles, PROFILES_FILE};
+mod storage;
+mod ratings;
+use ratings::{Ratings, RATINGS_FILE};
+mod event_log;
+mod self_play;
+use self_play::run_self_play;
+mod session;
+use session::Session;
+mod tournament;
+use tournament::{MatchFormat, Tournament};
+mod render_tournament;
+use render_tournament::{render_standings, StandingsAction};
+mod notation;
+mod replay;
+use replay::Replay;
+mod tutorial;
+use tutorial::{Tutorial, LESSONS_DIR};
+mod render_tutorial;
+mod clock;
+mod theme;
+mod skins;
+mod audio;
+mod history;
+use audio::SoundBank;
+mod i18n;
+use i18n::{Catalog, Language, LOCALES_DIR};
+use render_tutorial::{render_tutorial, TutorialAction};
 
 fn window_conf() -> Conf {
     Conf {
@@ -18,86 +56,335 @@ fn window_conf() -> Conf {
     }
 }
 
-#[macroquad::main(window_conf)]
-async fn main() {
-    // Create a game with 4 players and roll dice automatically
-    let mut game = Game::new();
-    roll_all_dice(&mut game);
-
-    // Create render state for UI controls
-    let mut render_state = RenderState::new();
-    
-    // Track timing for AI actions
-    let mut last_ai_action_time = std::time::Instant::now();
-    
-    // Track whether dice should be revealed (after a call action)
-    let mut dice_revealed = false;
-    let mut dice_revealed_time: Option<std::time::Instant> = None;
+/// Reads the table setup from the command line: `--spectate` for the public view,
+/// `--spectate-all` for the delayed view of every hand, `--humans N` for N hot-seat players.
+/// Without any of these the game opens on the main menu.
+fn setup_from_args() -> Option<(Viewer, usize)> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("--spectate") => Some((Viewer::Spectator, 0)),
+        Some("--spectate-all") => Some((Viewer::OmniscientSpectator, 0)),
+        Some("--humans") => {
+            let human_count = args.get(1).and_then(|count| count.parse().ok()).unwrap_or(1);
+            // With no human seats there is nobody to play for, so the table is watched instead
+            let viewer = if human_count == 0 { Viewer::Spectator } else { Viewer::Player(0) };
+            Some((viewer, human_count))
+        }
+        _ => None,
+    }
+}
+
+/// `--self-play N` plays N all-AI games without a window and prints the ratings leaderboard
+fn self_play_from_args() -> Option<u32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("--self-play") => Some(args.get(1).and_then(|count| count.parse().ok()).unwrap_or(100)),
+        _ => None,
+    }
+}
+
+/// `--replay FILE` steps through a saved transcript instead of playing
+fn replay_from_args() -> Option<PathBuf> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("--replay") => args.get(1).map(PathBuf::from),
+        _ => None,
+    }
+}
+
+fn load_settings() -> Settings {
+    Settings::load(SETTINGS_FILE).unwrap_or_else(|e| {
+        println!("Settings error: {}", e);
+        Settings::new()
+    })
+}
+
+/// Switches the screens to `language`, using the built-in catalog if its file can't be read
+fn use_language(language: Language) {
+    let catalog = Catalog::load(language, Path::new(LOCALES_DIR)).unwrap_or_else(|e| {
+        println!("Language error: {}", e);
+        Catalog::bundled(language)
+    });
+    i18n::use_catalog(catalog);
+}
+
+fn load_ratings(ratings_path: &Result<PathBuf, String>) -> Ratings {
+    ratings_path.clone().and_then(|path| Ratings::load(&path)).unwrap_or_else(|e| {
+        println!("Ratings error: {}", e);
+        Ratings::default()
+    })
+}
+
+fn main() {
+    match self_play_from_args() {
+        Some(game_count) => {
+            let ratings_path = storage::data_file(RATINGS_FILE);
+            let mut ratings = load_ratings(&ratings_path);
+            if let Err(e) = run_self_play(game_count, &load_settings(), &mut ratings) {
+                println!("Self-play error: {}", e);
+                return;
+            }
+            if let Err(e) = ratings_path.and_then(|path| ratings.save(&path)) {
+                println!("Ratings error: {}", e);
+            }
+
+            println!("{:<24} {:>8} {:>7}", "Name", "Rating", "Games");
+            for (name, rating) in ratings.leaderboard() {
+                println!("{:<24} {:>8.1} {:>7}", name, rating.rating, rating.games);
+            }
+        }
+        None => macroquad::Window::from_config(window_conf(), run()),
+    }
+}
+
+async fn run() {
+    let mut settings = load_settings();
+    use_language(settings.language);
+
+    // Command line setups skip the menu and play with the saved setup's other choices
+    let args_setup = setup_from_args();
+    let mut session = match args_setup {
+        Some((viewer, human_count)) => {
+            let mut game_settings = settings.clone();
+            game_settings.set_human_count(human_count);
+            Session::new(game_settings, Some(viewer))
+        }
+        None => Session::new(settings.clone(), None),
+    };
+    let mut replay = replay_from_args().and_then(|path| {
+        Replay::load(&path)
+            .map_err(|e| println!("Replay error: {}", e))
+            .ok()
+    });
+    // Only a command line setup is played straight away; otherwise the table waits behind
+    // the menu or a replay
+    if args_setup.is_some() && replay.is_none() {
+        session.start_event_log();
+    }
+    let mut menu = (args_setup.is_none() && replay.is_none()).then(Menu::new);
+
+    // Lessons in progress, played in place of a normal game
+    let mut tutorial: Option<Tutorial> = None;
+
+    // A match or tournament in progress, with the standings shown between its games
+    let mut tournament: Option<Tournament> = None;
+    let mut showing_standings = false;
+    let mut standings_message: Option<String> = None;
+
+    // Lifetime stats for the human seats, updated as each game ends
+    let profiles_path = storage::data_file(PROFILES_FILE);
+    let mut profiles = profiles_path.clone().and_then(|path| Profiles::load(&path)).unwrap_or_else(|e| {
+        println!("Profiles error: {}", e);
+        Profiles::default()
+    });
+
+    // One leaderboard for human profiles and AI strategies
+    let ratings_path = storage::data_file(RATINGS_FILE);
+    let mut ratings = load_ratings(&ratings_path);
+
+    let key_bindings = KeyBindings::load(KEY_BINDINGS_FILE).unwrap_or_else(|e| {
+        println!("Key bindings error: {}", e);
+        KeyBindings::new()
+    });
+    // The game plays on silently if the sounds can't be loaded
+    let sound_bank = SoundBank::load().await.map_err(|e| println!("Sound error: {}", e)).ok();
 
     loop {
-        // Check if dice should be hidden again after 3 seconds
-        if let Some(revealed_time) = dice_revealed_time {
-            let current_time = std::time::Instant::now();
-            if current_time.duration_since(revealed_time) >= std::time::Duration::from_secs(3) {
-                dice_revealed = false;
-                dice_revealed_time = None;
+        // Main menu, setup and settings screens
+        if let Some(current_menu) = &mut menu {
+            let action = render_menu(current_menu, &mut settings, &profiles, &ratings);
+            if settings.language != i18n::language() {
+                use_language(settings.language);
+            }
+            match action {
+                Some(MenuAction::StartGame) => {
+                    if let Err(e) = settings.save(SETTINGS_FILE) {
+                        println!("Settings error: {}", e);
+                    }
+                    tournament = (settings.match_format != MatchFormat::Single).then(|| Tournament::from_settings(&settings));
+                    let game_settings = tournament
+                        .as_ref()
+                        .and_then(|tournament| tournament.table_settings(&settings))
+                        .unwrap_or_else(|| settings.clone());
+                    session = Session::new(game_settings, None);
+                    session.start_event_log();
+                    menu = None;
+                }
+                Some(MenuAction::StartTutorial) => match Tutorial::load(settings.language, Path::new(LESSONS_DIR)) {
+                    Ok(new_tutorial) => {
+                        session = Session::with_game(new_tutorial.new_game(), new_tutorial.settings(&settings), None);
+                        tutorial = Some(new_tutorial);
+                        menu = None;
+                    }
+                    Err(e) => println!("Tutorial error: {}", e),
+                },
+                Some(MenuAction::SaveSettings) => {
+                    if let Err(e) = settings.save(SETTINGS_FILE) {
+                        println!("Settings error: {}", e);
+                    }
+                }
+                Some(MenuAction::Quit) => break,
+                None => {}
+            }
+            next_frame().await;
+            continue;
+        }
+
+        // Standings between the games of a match
+        if let (true, Some(current_tournament)) = (showing_standings, &tournament) {
+            match render_standings(current_tournament, &settings.palette(), standings_message.as_deref()) {
+                Some(StandingsAction::NextGame) => {
+                    if let Some(game_settings) = current_tournament.table_settings(&settings) {
+                        session = Session::new(game_settings, None);
+                        session.start_event_log();
+                        showing_standings = false;
+                        standings_message = None;
+                    }
+                }
+                Some(StandingsAction::SaveResults) => {
+                    let file_name = format!("tournament-{}.txt", unix_time());
+                    let saved = storage::data_file(&file_name)
+                        .and_then(|path| current_tournament.save_results(&path).map(|()| path));
+                    standings_message = Some(match saved {
+                        Ok(path) => i18n::tr_args("standings.saved", &[("path", &path.display())]),
+                        Err(e) => e,
+                    });
+                }
+                Some(StandingsAction::MainMenu) => {
+                    tournament = None;
+                    showing_standings = false;
+                    standings_message = None;
+                    menu = Some(Menu::new());
+                }
+                None => {}
+            }
+            next_frame().await;
+            continue;
+        }
+
+        // A transcript being stepped through, one move at a time
+        if let Some(current_replay) = &mut replay {
+            if is_key_pressed(KeyCode::Right) {
+                current_replay.step(1);
+            } else if is_key_pressed(KeyCode::Left) {
+                current_replay.step(-1);
+            }
+            render_game(current_replay.current(), &mut session.render_state, &key_bindings, Viewer::Replay, None);
+
+            let finished = current_replay.current().winner.is_some();
+            if finished && handle_restart_click() {
+                current_replay.position = 0;
+            } else if finished && handle_save_game_click() {
+                save_transcript(current_replay.current());
+            } else if is_key_pressed(KeyCode::Escape) || (finished && handle_main_menu_click()) {
+                replay = None;
+                menu = Some(Menu::new());
+            }
+            next_frame().await;
+            continue;
+        }
+
+        session.clock.tick(get_frame_time());
+        session.expire_notice();
+
+        // Advance the current animation, or jump to its end when the skip key is pressed
+        if let Some(current_animation) = &mut session.animation {
+            current_animation.update(get_frame_time());
+            if key_bindings.pressed_commands().contains(&keybindings::Command::SkipAnimation) {
+                current_animation.skip();
+            }
+            if current_animation.is_finished() {
+                session.animation = None;
+            }
+        }
+        let revealing = session.animation.as_ref().is_some_and(|animation| animation.is_revealing());
+
+        // Once the reveal has played out, deal the next round. Tutorials keep the round on
+        // the table until the lesson is done with it, and deal their own hands.
+        let held = tutorial.as_ref().is_some_and(|current_tutorial| current_tutorial.holds_table(&session.game));
+        if let Some(current_tutorial) = &mut tutorial {
+            current_tutorial.prepare_deal(&mut session.game);
+        }
+        if session.game.winner.is_none() && !revealing && !held && session.game.phase != Phase::Bidding {
+            if let Err(e) = session.advance_phase() {
+                println!("Phase error: {}", e);
+            }
+        }
+
+        // Play the sounds for whatever has happened at the table
+        for effect in session.due_sounds() {
+            if let Some(bank) = &sound_bank {
+                bank.play(effect, &settings.mixer);
             }
         }
         
+        // In hot-seat games, hide the table until the next human has the device
+        let game = &session.game;
+        if session.hot_seat && game.winner.is_none() && game.phase == Phase::Bidding && session.handoff_to.is_none()
+            && game.is_human[game.current_player as usize]
+            && session.viewer != Viewer::Player(game.current_player)
+        {
+            session.viewer = Viewer::Hidden;
+            session.handoff_to = Some(game.current_player);
+        }
+
+        if let Some(next_player) = session.handoff_to {
+            render_handoff_screen(&session.game, next_player, &session.render_state.palette);
+            if handle_handoff_click() {
+                session.viewer = Viewer::Player(next_player);
+                session.handoff_to = None;
+            }
+            next_frame().await;
+            continue;
+        }
+
+        // Play the default move for a human who has run out of time
+        let table_ready = session.animation.is_none() && session.viewer != Viewer::Hidden;
+        match session.update_turn_timer(table_ready) {
+            Ok(true) if session.hot_seat => session.viewer = Viewer::Hidden,
+            Ok(_) => {}
+            Err(e) => println!("Timeout error: {}", e),
+        }
+
         // Always render the game
-        let action = render_game(&game, &mut render_state, dice_revealed);
+        let action = render_game(&session.game, &mut session.render_state, &key_bindings, session.viewer, session.animation.as_ref());
 
-        // Only allow human (player 0) to act when it's their turn and the game is not over
-        if game.current_player == 0 && game.winner.is_none() {
+        // Only allow a human to act from their own seat when it's their turn and the game is not over.
+        // Nobody acts until the next round has been dealt.
+        let game = &session.game;
+        if game.phase == Phase::Bidding && game.is_human[game.current_player as usize] && game.winner.is_none() {
+            let action = action.filter(|_| session.viewer.seat() == Some(game.current_player));
+            // A lesson only accepts the move it is teaching
+            let action = action.filter(|action| tutorial.as_mut().is_none_or(|current_tutorial| current_tutorial.try_move(action)));
             if let Some(action) = action {
-                match take_action(&game, &action) {
-                    Ok(new_game) => {
-                        match action.action {
-                            game::Action::Bet => {
-                                if let Some((_, dice_count, face_value)) = new_game.bets.last() {
-                                    render_state.selected_dice_count = *dice_count;
-                                    render_state.selected_face_value = *face_value;
-                                }
-                            }
-                            game::Action::Call => {
-                                render_state.selected_dice_count = 1;
-                                render_state.selected_face_value = 1;
-                                dice_revealed = true; // Reveal dice after a call
-                                dice_revealed_time = Some(std::time::Instant::now()); // Start timing
-                            }
+                match session.apply_action(&action) {
+                    Ok(()) => {
+                        // Hide this player's dice as soon as their turn is over
+                        if session.hot_seat {
+                            session.viewer = Viewer::Hidden;
                         }
-                        game = new_game;
                     }
                     Err(e) => println!("Action error: {}", e),
                 }
             }
-        } else if game.winner.is_none() {
-            // AI takes actions for players 1-3
-            while game.current_player != 0 && game.winner.is_none() {
+        } else if game.phase == Phase::Bidding && game.winner.is_none() {
+            // AI takes actions for every seat without a human
+            while session.game.phase == Phase::Bidding && !session.game.is_human[session.game.current_player as usize] && session.game.winner.is_none() {
                 // Check if enough time has passed since last AI action
-                let current_time = std::time::Instant::now();
-                let time_since_last_action = current_time.duration_since(last_ai_action_time);
-                
-                if time_since_last_action >= std::time::Duration::from_millis(1000) {
-                    let ai_action = ai_decide_action(&game);
-                    match take_action(&game, &ai_action) {
-                        Ok(new_game) => {
-                            match ai_action.action {
-                                game::Action::Bet => {
-                                    if let Some((_, dice_count, face_value)) = new_game.bets.last() {
-                                        render_state.selected_dice_count = *dice_count;
-                                        render_state.selected_face_value = *face_value;
-                                    }
-                                }
-                                game::Action::Call => {
-                                    render_state.selected_dice_count = 1;
-                                    render_state.selected_face_value = 1;
-                                    dice_revealed = true; // Reveal dice after a call
-                                    dice_revealed_time = Some(std::time::Instant::now()); // Start timing
-                                }
+                if session.ai_ready() {
+                    // Tutorial seats play their script, and wait while a callout is up
+                    let ai_action = match &tutorial {
+                        Some(current_tutorial) => match current_tutorial.scripted_move(&session.game) {
+                            Some(action) => action,
+                            None => break,
+                        },
+                        None => ai_decide_action(&session.game, session.settings.ai_difficulty(session.game.current_player)),
+                    };
+                    match session.apply_action(&ai_action) {
+                        Ok(()) => {
+                            if let Some(current_tutorial) = &mut tutorial {
+                                current_tutorial.played_scripted_move();
                             }
-                            game = new_game;
-                            last_ai_action_time = current_time; // Update the last action time
                         }
                         Err(e) => {
                             println!("AI error: {}", e);
@@ -111,16 +398,92 @@ async fn main() {
             }
         }
 
+        // Callouts over the table, and moving between lessons
+        if let Some(current_tutorial) = &mut tutorial {
+            match render_tutorial(current_tutorial, &session.game, &session.render_state.palette) {
+                Some(TutorialAction::DismissNote) => current_tutorial.dismiss_note(),
+                Some(TutorialAction::NextLesson) => {
+                    current_tutorial.next_lesson();
+                    session = Session::with_game(current_tutorial.new_game(), current_tutorial.settings(&settings), None);
+                }
+                Some(TutorialAction::MainMenu) => {
+                    tutorial = None;
+                    menu = Some(Menu::new());
+                }
+                None => {}
+            }
+            next_frame().await;
+            continue;
+        }
+
+        // Add each finished game to the players' lifetime stats and ratings once
+        if session.game.winner.is_some() && !session.recorded {
+            session.recorded = true;
+            profiles.record_game(&session.game);
+            if let Err(e) = profiles_path.clone().and_then(|path| profiles.save(&path)) {
+                println!("Profiles error: {}", e);
+            }
+            if session.settings.rated {
+                ratings.record_game(&session.game, &session.settings.seats);
+                if let Err(e) = ratings_path.clone().and_then(|path| ratings.save(&path)) {
+                    println!("Ratings error: {}", e);
+                }
+            }
+
+            // In a match, go straight to the standings
+            if let Some(current_tournament) = &mut tournament {
+                current_tournament.record_game(&session.game);
+                showing_standings = true;
+            }
+        }
+
+        // Hurry the AI along to the human's next turn or the end of the round
+        if key_bindings.pressed_commands().contains(&keybindings::Command::FastForward) {
+            session.start_fast_forward();
+        }
+
+        // Take back the human's last move in a casual game
+        if tutorial.is_none() && key_bindings.pressed_commands().contains(&keybindings::Command::Undo) {
+            if session.undo_allowed() {
+                session.undo();
+            } else {
+                session.show_notice(i18n::tr("game.takeback_not_allowed"));
+            }
+        }
+
         // Check for restart button click when there's a winner
-        if game.winner.is_some() && handle_restart_click() {
-            // Reset the game
-            game = Game::new();
-            roll_all_dice(&mut game);
-            render_state = RenderState::new();
-            dice_revealed = false; // Reset dice visibility
-            dice_revealed_time = None; // Reset timing
+        if session.game.winner.is_some() && handle_restart_click() {
+            // Reset the game with the same setup
+            session.restart();
+        } else if session.game.winner.is_some() && handle_main_menu_click() {
+            menu = Some(Menu::new());
+        } else if session.game.winner.is_some() && handle_stats_click() {
+            menu = Some(Menu::at(MenuScreen::Stats));
+        } else if session.game.winner.is_some() && handle_save_game_click() {
+            save_transcript(&session.game);
         }
 
         next_frame().await;
     }
-} 
\ No newline at end of file
+}
+
+/// Writes a game's transcript to the data directory
+fn save_transcript(game: &game::Game) {
+    let file_name = format!("game-{}.txt", unix_time());
+    let saved = storage::data_file(&file_name)
+        .and_then(|path| match std::fs::write(&path, notation::export(game)) {
+            Ok(()) => Ok(path),
+            Err(e) => Err(format!("Could not write {}: {}", path.display(), e)),
+        });
+    match saved {
+        Ok(path) => println!("Game saved to {}", path.display()),
+        Err(e) => println!("Save error: {}", e),
+    }
+}
+
+/// Seconds since the Unix epoch, for naming saved files
+fn unix_time() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
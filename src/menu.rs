@@ -0,0 +1,431 @@
+// Main menu, new game setup and settings screens for Rusty Dice
+
+use macroquad::prelude::*;
+
+use crate::audio::Channel;
+use crate::i18n::{tr, tr_args};
+use crate::profiles::Profiles;
+use crate::ratings::Ratings;
+use crate::settings::{name_length, SeatKind, Settings, MAX_NAME_LENGTH};
+use crate::skins::{custom_skin_names, DICE_SKINS_DIR};
+use crate::theme::Palette;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuScreen {
+    Main,
+    Setup,
+    Settings,
+    Stats,
+    Leaderboard,
+}
+
+/// What the player chose on the menu this frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    StartGame,
+    StartTutorial,
+    SaveSettings, // Left the setup or settings screen
+    Quit,
+}
+
+pub struct Menu {
+    pub screen: MenuScreen,
+    editing_name: Option<usize>, // Seat whose name is being typed
+}
+
+impl Menu {
+    pub fn new() -> Self {
+        Self::at(MenuScreen::Main)
+    }
+
+    /// Opens the menu on a particular screen
+    pub fn at(screen: MenuScreen) -> Self {
+        Self {
+            screen,
+            editing_name: None,
+        }
+    }
+}
+
+/// Draws the current menu screen and applies any changes to the settings
+pub fn render_menu(menu: &mut Menu, settings: &mut Settings, profiles: &Profiles, ratings: &Ratings) -> Option<MenuAction> {
+    let palette = &settings.palette();
+    clear_background(palette.background);
+    let scale = (screen_width() / 1200.0).min(screen_height() / 800.0);
+
+    match menu.screen {
+        MenuScreen::Main => render_main_menu(menu, scale, palette),
+        MenuScreen::Setup => render_setup(menu, settings, scale, palette),
+        MenuScreen::Settings => render_settings(menu, settings, scale, palette),
+        MenuScreen::Stats => render_stats(menu, profiles, scale, palette),
+        MenuScreen::Leaderboard => render_leaderboard(menu, ratings, scale, palette),
+    }
+}
+
+fn render_main_menu(menu: &mut Menu, scale: f32, palette: &Palette) -> Option<MenuAction> {
+    let center_x = screen_width() / 2.0;
+    draw_title("Rusty Dice", center_x, screen_height() / 2.0 - 140.0 * scale, scale, palette);
+
+    let button = |row: f32| Rect::new(center_x - 100.0 * scale, screen_height() / 2.0 + row * 70.0 * scale - 60.0 * scale, 200.0 * scale, 50.0 * scale);
+    if menu_button(button(0.0), &tr("menu.new_game"), scale, palette) {
+        menu.screen = MenuScreen::Setup;
+    }
+    if menu_button(button(1.0), &tr("menu.tutorial"), scale, palette) {
+        return Some(MenuAction::StartTutorial);
+    }
+    if menu_button(button(2.0), &tr("menu.settings"), scale, palette) {
+        menu.screen = MenuScreen::Settings;
+    }
+    if menu_button(button(3.0), &tr("common.stats"), scale, palette) {
+        menu.screen = MenuScreen::Stats;
+    }
+    if menu_button(button(4.0), &tr("menu.leaderboard"), scale, palette) {
+        menu.screen = MenuScreen::Leaderboard;
+    }
+    if menu_button(button(5.0), &tr("menu.quit"), scale, palette) {
+        return Some(MenuAction::Quit);
+    }
+    None
+}
+
+fn render_setup(menu: &mut Menu, settings: &mut Settings, scale: f32, palette: &Palette) -> Option<MenuAction> {
+    draw_title(&tr("menu.new_game"), screen_width() / 2.0, 60.0 * scale, scale, palette);
+
+    let left = screen_width() / 2.0 - 400.0 * scale;
+    let row = |index: f32| 110.0 * scale + index * 45.0 * scale;
+
+    // Table size and rules
+    if let Some(step) = stepper(left, row(0.0), &tr("setup.players"), &settings.player_count.to_string(), scale, palette) {
+        settings.step_player_count(step);
+    }
+    if let Some(step) = stepper(left, row(1.0), &tr("setup.starting_dice"), &settings.rules.starting_dice.to_string(), scale, palette) {
+        settings.step_starting_dice(step);
+    }
+    draw_label(&tr("setup.ai_pacing"), left, row(2.0), scale, palette);
+    if menu_button(value_rect(left, row(2.0), scale), &settings.ai_pacing.label(), scale, palette) {
+        settings.ai_pacing = settings.ai_pacing.next();
+    }
+    draw_label(&tr("setup.ones_wild"), left, row(3.0), scale, palette);
+    if menu_button(value_rect(left, row(3.0), scale), &on_off(settings.rules.ones_wild), scale, palette) {
+        settings.rules.ones_wild = !settings.rules.ones_wild;
+    }
+
+    // Single game, match or tournament
+    draw_label(&tr("setup.format"), left, row(4.0), scale, palette);
+    if menu_button(value_rect(left, row(4.0), scale), &settings.match_format.label(), scale, palette) {
+        settings.match_format = settings.match_format.next();
+    }
+    if settings.match_format.uses_tables() {
+        if let Some(step) = stepper(left, row(5.0), &tr("setup.table_size"), &settings.table_size.to_string(), scale, palette) {
+            settings.step_table_size(step);
+        }
+    }
+
+    // Shot clock for human turns
+    let limit_label = match settings.turn_time_limit {
+        0 => tr("common.off"),
+        limit => tr_args("common.seconds", &[("seconds", &limit)]),
+    };
+    if let Some(step) = stepper(left, row(8.0), &tr("setup.turn_time_limit"), &limit_label, scale, palette) {
+        settings.step_turn_time_limit(step);
+    }
+    if settings.turn_time_limit > 0 {
+        draw_label(&tr("setup.on_timeout"), left, row(9.0), scale, palette);
+        if menu_button(value_rect(left, row(9.0), scale), &settings.timeout_action.label(), scale, palette) {
+            settings.timeout_action = settings.timeout_action.next();
+        }
+    }
+
+    // Hints are for practice, so a game that shows them isn't rated
+    draw_label(&tr("setup.rated"), left, row(6.0), scale, palette);
+    if menu_button(value_rect(left, row(6.0), scale), &on_off(settings.rated), scale, palette) {
+        settings.toggle_rated();
+    }
+    draw_label(&tr("setup.hints"), left, row(7.0), scale, palette);
+    if menu_button(value_rect(left, row(7.0), scale), &on_off(settings.hints), scale, palette) {
+        settings.toggle_hints();
+    }
+
+    // One line per seat: name field and who plays it
+    let seats_left = screen_width() / 2.0 + 20.0 * scale;
+    draw_label(&tr("setup.seats"), seats_left, row(0.0), scale, palette);
+    for player in 0..settings.player_count as usize {
+        let y = row(1.0 + player as f32 * 0.8);
+        let name_rect = Rect::new(seats_left, y - 26.0 * scale, 220.0 * scale, 32.0 * scale);
+        let editing = menu.editing_name == Some(player);
+        draw_rectangle(name_rect.x, name_rect.y, name_rect.w, name_rect.h, if editing { palette.background } else { palette.panel });
+        if editing {
+            draw_rectangle_lines(name_rect.x, name_rect.y, name_rect.w, name_rect.h, 2.0, palette.focus);
+        }
+        draw_text(&settings.player_names[player], name_rect.x + 8.0 * scale, y, 22.0 * scale, palette.text);
+        if clicked(name_rect) {
+            stop_editing(menu, settings);
+            menu.editing_name = Some(player);
+        }
+
+        let kind_rect = Rect::new(name_rect.right() + 10.0 * scale, name_rect.y, 110.0 * scale, name_rect.h);
+        let kind = settings.seats[player];
+        let kind_label = match kind {
+            SeatKind::Human => tr("setup.human"),
+            SeatKind::Ai(difficulty) => tr_args("setup.ai", &[("difficulty", &tr(&format!("difficulty.{}", difficulty.name())))]),
+        };
+        if menu_button(kind_rect, &kind_label, scale, palette) {
+            settings.seats[player] = kind.next();
+        }
+
+        // Dice skin, including any custom skins in the skins folder
+        let skin_rect = Rect::new(kind_rect.right() + 10.0 * scale, name_rect.y, 120.0 * scale, name_rect.h);
+        if menu_button(skin_rect, &settings.dice_skins[player].label(), scale, palette) {
+            settings.dice_skins[player] = settings.dice_skins[player].next(&custom_skin_names(std::path::Path::new(DICE_SKINS_DIR)));
+        }
+    }
+    edit_name(menu, settings);
+
+    let bottom = screen_height() - 80.0 * scale;
+    if menu_button(Rect::new(left, bottom, 160.0 * scale, 50.0 * scale), &tr("common.back"), scale, palette) {
+        menu.screen = MenuScreen::Main;
+        stop_editing(menu, settings);
+        return Some(MenuAction::SaveSettings);
+    }
+    if menu_button(Rect::new(screen_width() / 2.0 + 240.0 * scale, bottom, 160.0 * scale, 50.0 * scale), &tr("setup.start_game"), scale, palette) {
+        stop_editing(menu, settings);
+        return Some(MenuAction::StartGame);
+    }
+    None
+}
+
+fn render_settings(menu: &mut Menu, settings: &mut Settings, scale: f32, palette: &Palette) -> Option<MenuAction> {
+    draw_title(&tr("menu.settings"), screen_width() / 2.0, 60.0 * scale, scale, palette);
+
+    let left = screen_width() / 2.0 - 220.0 * scale;
+    let row = |index: f32| 150.0 * scale + index * 50.0 * scale;
+
+    if let Some(step) = stepper(left, row(0.0), &tr("settings.reveal_duration"), &tr_args("common.seconds", &[("seconds", &format!("{:.1}", settings.reveal_duration))]), scale, palette) {
+        settings.step_reveal_duration(step as f32);
+    }
+    if let Some(step) = stepper(left, row(1.0), &tr("settings.animation_speed"), &format!("{:.2}x", settings.animation_speed), scale, palette) {
+        settings.step_animation_speed(step as f32);
+    }
+    draw_label(&tr("settings.theme"), left, row(2.0), scale, palette);
+    if menu_button(value_rect(left, row(2.0), scale), &settings.theme.label(), scale, palette) {
+        settings.theme = settings.theme.next();
+    }
+    draw_label(&tr("settings.colorblind"), left, row(3.0), scale, palette);
+    if menu_button(value_rect(left, row(3.0), scale), &on_off(settings.colorblind), scale, palette) {
+        settings.colorblind = !settings.colorblind;
+    }
+
+    // Sound effects
+    if let Some(volume) = slider(left, row(4.0), &tr("settings.master_volume"), settings.mixer.master, scale, palette) {
+        settings.mixer.master = volume;
+    }
+    for (i, channel) in Channel::ALL.into_iter().enumerate() {
+        if let Some(volume) = slider(left, row(5.0 + i as f32), &channel.label(), settings.mixer.channel_volume(channel), scale, palette) {
+            settings.mixer.set_channel_volume(channel, volume);
+        }
+    }
+    draw_label(&tr("settings.sound"), left, row(8.0), scale, palette);
+    let sound_label = if settings.mixer.muted { tr("settings.muted") } else { tr("common.on") };
+    if menu_button(value_rect(left, row(8.0), scale), &sound_label, scale, palette) {
+        settings.mixer.muted = !settings.mixer.muted;
+    }
+
+    // Each language is labelled in itself; the screens switch over as soon as it changes
+    draw_label(&tr("settings.language"), left, row(9.0), scale, palette);
+    if menu_button(value_rect(left, row(9.0), scale), settings.language.label(), scale, palette) {
+        settings.language = settings.language.next();
+    }
+    draw_label(&tr("settings.event_log"), left, row(10.0), scale, palette);
+    if menu_button(value_rect(left, row(10.0), scale), &on_off(settings.event_log), scale, palette) {
+        settings.event_log = !settings.event_log;
+    }
+
+    if menu_button(Rect::new(left, screen_height() - 80.0 * scale, 160.0 * scale, 50.0 * scale), &tr("common.back"), scale, palette) {
+        menu.screen = MenuScreen::Main;
+        return Some(MenuAction::SaveSettings);
+    }
+    None
+}
+
+/// Lifetime stats for every local profile, one row each
+fn render_stats(menu: &mut Menu, profiles: &Profiles, scale: f32, palette: &Palette) -> Option<MenuAction> {
+    draw_title(&tr("common.stats"), screen_width() / 2.0, 60.0 * scale, scale, palette);
+
+    let columns = [
+        (tr("common.name"), 0.0),
+        (tr("stats.played"), 200.0),
+        (tr("stats.won"), 290.0),
+        (tr("stats.average_place"), 360.0),
+        (tr("stats.bluffs_won"), 480.0),
+        (tr("stats.calls_right"), 610.0),
+        (tr("stats.lost_calling"), 750.0),
+        (tr("stats.lost_bidding"), 900.0),
+    ];
+    let left = screen_width() / 2.0 - 520.0 * scale;
+    let row = |index: f32| 130.0 * scale + index * 34.0 * scale;
+    for (heading, x) in &columns {
+        draw_text(heading, left + x * scale, row(0.0), 22.0 * scale, palette.muted_text);
+    }
+
+    if profiles.profiles.is_empty() {
+        draw_label(&tr("stats.empty"), left, row(1.5), scale, palette);
+    }
+
+    let percent = |rate: Option<f32>| rate.map_or("-".to_string(), |rate| format!("{:.0}%", rate * 100.0));
+    for (i, (name, stats)) in profiles.profiles.iter().enumerate() {
+        let values = [
+            name.clone(),
+            stats.games_played.to_string(),
+            stats.games_won.to_string(),
+            stats.average_finish().map_or("-".to_string(), |place| format!("{:.1}", place)),
+            percent(stats.bluff_success_rate()),
+            percent(stats.call_accuracy()),
+            stats.dice_lost_calling.to_string(),
+            stats.dice_lost_bidding.to_string(),
+        ];
+        for ((_, x), value) in columns.iter().zip(&values) {
+            draw_text(value, left + x * scale, row(i as f32 + 1.0), 22.0 * scale, palette.text);
+        }
+    }
+
+    if menu_button(Rect::new(left, screen_height() - 80.0 * scale, 160.0 * scale, 50.0 * scale), &tr("common.back"), scale, palette) {
+        menu.screen = MenuScreen::Main;
+    }
+    None
+}
+
+/// Ratings for human profiles and AI strategies, highest first
+fn render_leaderboard(menu: &mut Menu, ratings: &Ratings, scale: f32, palette: &Palette) -> Option<MenuAction> {
+    draw_title(&tr("menu.leaderboard"), screen_width() / 2.0, 60.0 * scale, scale, palette);
+
+    let left = screen_width() / 2.0 - 250.0 * scale;
+    let row = |index: f32| 130.0 * scale + index * 34.0 * scale;
+    let columns = [(tr("common.name"), 0.0), (tr("leaderboard.rating"), 320.0), (tr("common.games"), 430.0)];
+    for (heading, x) in &columns {
+        draw_text(heading, left + x * scale, row(0.0), 22.0 * scale, palette.muted_text);
+    }
+
+    let leaderboard = ratings.leaderboard();
+    if leaderboard.is_empty() {
+        draw_label(&tr("leaderboard.empty"), left, row(1.5), scale, palette);
+    }
+
+    // As many rows as fit above the Back button
+    let visible_rows = ((screen_height() - 110.0 * scale - row(1.0)) / (34.0 * scale)).max(0.0) as usize;
+    for (i, (name, rating)) in leaderboard.iter().take(visible_rows).enumerate() {
+        let values = [name.clone(), format!("{:.0}", rating.rating), rating.games.to_string()];
+        for ((_, x), value) in columns.iter().zip(&values) {
+            draw_text(value, left + x * scale, row(i as f32 + 1.0), 22.0 * scale, palette.text);
+        }
+    }
+
+    if menu_button(Rect::new(left, screen_height() - 80.0 * scale, 160.0 * scale, 50.0 * scale), &tr("common.back"), scale, palette) {
+        menu.screen = MenuScreen::Main;
+    }
+    None
+}
+
+/// Types into the name field being edited; Enter or Escape finishes
+fn edit_name(menu: &mut Menu, settings: &mut Settings) {
+    let Some(player) = menu.editing_name else {
+        // Drop anything typed while no field had focus
+        while get_char_pressed().is_some() {}
+        return;
+    };
+    let name = &mut settings.player_names[player];
+
+    while let Some(character) = get_char_pressed() {
+        if !character.is_control() && name_length(name) < MAX_NAME_LENGTH {
+            name.push(character);
+        }
+    }
+    if is_key_pressed(KeyCode::Backspace) {
+        name.pop();
+    }
+    if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::Escape) {
+        stop_editing(menu, settings);
+    }
+}
+
+/// Leaves the name field, putting back the default name if it was left empty
+fn stop_editing(menu: &mut Menu, settings: &mut Settings) {
+    if let Some(player) = menu.editing_name.take() {
+        if settings.player_names[player].trim().is_empty() {
+            settings.player_names[player] = format!("Player {}", player + 1);
+        }
+    }
+}
+
+fn on_off(on: bool) -> String {
+    tr(if on { "common.on" } else { "common.off" })
+}
+
+pub fn draw_title(title: &str, center_x: f32, y: f32, scale: f32, palette: &Palette) {
+    let font_size = 50.0 * scale;
+    let text_size = measure_text(title, None, font_size as u16, 1.0);
+    draw_text(title, center_x - text_size.width / 2.0, y, font_size, palette.text);
+}
+
+fn draw_label(label: &str, x: f32, y: f32, scale: f32, palette: &Palette) {
+    draw_text(label, x, y, 24.0 * scale, palette.text);
+}
+
+/// Where a setting's value or toggle button sits, right of its label
+fn value_rect(left: f32, y: f32, scale: f32) -> Rect {
+    Rect::new(left + 220.0 * scale, y - 26.0 * scale, 140.0 * scale, 32.0 * scale)
+}
+
+/// A label with a value between - and + buttons, returning the step that was clicked
+fn stepper(left: f32, y: f32, label: &str, value: &str, scale: f32, palette: &Palette) -> Option<i8> {
+    draw_label(label, left, y, scale, palette);
+
+    let value_area = value_rect(left, y, scale);
+    let minus = Rect::new(value_area.x, value_area.y, 32.0 * scale, value_area.h);
+    let plus = Rect::new(value_area.right() - 32.0 * scale, value_area.y, 32.0 * scale, value_area.h);
+    let text_size = measure_text(value, None, (22.0 * scale) as u16, 1.0);
+    draw_text(value, value_area.center().x - text_size.width / 2.0, y, 22.0 * scale, palette.text);
+
+    if menu_button(minus, "-", scale, palette) {
+        Some(-1)
+    } else if menu_button(plus, "+", scale, palette) {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+/// A label with a bar for a value from 0 to 1, returning the value under the mouse while
+/// the bar is clicked or dragged
+fn slider(left: f32, y: f32, label: &str, value: f32, scale: f32, palette: &Palette) -> Option<f32> {
+    draw_label(label, left, y, scale, palette);
+
+    let area = value_rect(left, y, scale);
+    let track = Rect::new(area.x, area.center().y - 4.0 * scale, area.w, 8.0 * scale);
+    draw_rectangle(track.x, track.y, track.w, track.h, palette.panel);
+    draw_rectangle(track.x, track.y, track.w * value, track.h, palette.button);
+    draw_circle(track.x + track.w * value, track.center().y, 10.0 * scale, palette.button_hover);
+    draw_text(&format!("{:.0}%", value * 100.0), area.right() + 16.0 * scale, y, 22.0 * scale, palette.text);
+
+    let mouse = Vec2::from(mouse_position());
+    (is_mouse_button_down(MouseButton::Left) && area.contains(mouse)).then(|| ((mouse.x - track.x) / track.w).clamp(0.0, 1.0))
+}
+
+/// Draws a button and returns whether it was clicked this frame
+pub fn menu_button(rect: Rect, label: &str, scale: f32, palette: &Palette) -> bool {
+    let hovered = rect.contains(Vec2::from(mouse_position()));
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, if hovered { palette.button_hover } else { palette.button });
+    let font_size = 22.0 * scale;
+    let text_size = measure_text(label, None, font_size as u16, 1.0);
+    draw_text(
+        label,
+        rect.center().x - text_size.width / 2.0,
+        rect.center().y + text_size.height / 2.0,
+        font_size,
+        palette.button_text,
+    );
+    clicked(rect)
+}
+
+fn clicked(rect: Rect) -> bool {
+    is_mouse_button_pressed(MouseButton::Left) && rect.contains(Vec2::from(mouse_position()))
+}
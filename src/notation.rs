@@ -0,0 +1,479 @@
+// Plain text transcripts of a game, for sharing and replaying.
+//
+//     # Rusty Dice transcript
+//     Rules: 5 dice, ones wild
+//     Seed: 1234
+//     P1 = Ana
+//     P2 = Player 2
+//
+//     Round 1
+//     Hands: P1 35512, P2 66421
+//     P1: 2x5
+//     P2: call
+//     Result: 3 counted (1 wild), P2 loses a die
+//
+// A round lists every hand at the deal, then each bid as `player: count x face` and the call,
+// or `P2: timeout` for a player who ran out of time and lost a die.
+// The lines after a call (`Result`, `P2 is out`, `Winner: P1`) are worked out again when a
+// transcript is read, so one that doesn't match its dice is rejected.
+
+use std::collections::VecDeque;
+
+use crate::game::{
+    advance_phase, apply_timeout, roll_all_dice, take_action, Action, Game, GameEvent, Phase, PlayerAction, RoundHands, RoundResolution, Rules,
+    TimeoutAction, MAX_PLAYERS,
+};
+
+const TITLE: &str = "# Rusty Dice transcript";
+
+fn player_tag(player: u8) -> String {
+    format!("P{}", player + 1)
+}
+
+pub fn parse_player_tag(tag: &str) -> Option<u8> {
+    let number: usize = tag.strip_prefix('P')?.parse().ok()?;
+    (1..=MAX_PLAYERS).contains(&number).then(|| (number - 1) as u8)
+}
+
+fn rules_line(rules: &Rules) -> String {
+    let wild = if rules.ones_wild { ", ones wild" } else { "" };
+    format!("Rules: {} dice{}", rules.starting_dice, wild)
+}
+
+/// Reads the value of a `Rules:` line, such as `5 dice, ones wild`
+pub fn parse_rules(value: &str) -> Option<Rules> {
+    let (dice, options) = match value.split_once(',') {
+        Some((dice, options)) => (dice, Some(options.trim())),
+        None => (value, None),
+    };
+    let starting_dice = dice.trim().strip_suffix(" dice")?.parse().ok()?;
+    let ones_wild = match options {
+        Some("ones wild") => true,
+        Some(_) => return None,
+        None => false,
+    };
+    Some(Rules { ones_wild, starting_dice })
+}
+
+fn hands_line(hands: &RoundHands, player_count: u8) -> String {
+    let listed: Vec<String> = (0..player_count)
+        .filter(|&player| hands.dice_count[player as usize] > 0)
+        .map(|player| {
+            let dice = &hands.player_dice[player as usize][..hands.dice_count[player as usize] as usize];
+            let faces: String = dice.iter().map(|die| die.to_string()).collect();
+            format!("{} {}", player_tag(player), faces)
+        })
+        .collect();
+    format!("Hands: {}", listed.join(", "))
+}
+
+/// The transcript lines for one event
+fn event_lines(event: &GameEvent, rules: &Rules) -> Vec<String> {
+    match event {
+        GameEvent::Bet { player, dice_count, face_value } => {
+            vec![format!("{}: {}x{}", player_tag(*player), dice_count, face_value)]
+        }
+        GameEvent::Call(resolution) => {
+            let wild = if rules.ones_wild {
+                format!(" ({} wild)", resolution.wild_count)
+            } else {
+                String::new()
+            };
+            vec![
+                format!("{}: call", player_tag(resolution.caller)),
+                format!(
+                    "Result: {} counted{}, {} loses a die",
+                    resolution.actual_count,
+                    wild,
+                    player_tag(resolution.losing_player)
+                ),
+            ]
+        }
+        GameEvent::TimedOut { player, .. } => vec![
+            format!("{}: timeout", player_tag(*player)),
+            format!("Result: {} ran out of time and loses a die", player_tag(*player)),
+        ],
+        GameEvent::Eliminated(elimination) => vec![format!("{} is out", player_tag(elimination.player))],
+        GameEvent::Won { player } => vec![format!("Winner: {}", player_tag(*player))],
+    }
+}
+
+/// Writes a game, finished or not, as a transcript
+pub fn export(game: &Game) -> String {
+    let mut lines = vec![TITLE.to_string(), rules_line(&game.rules), format!("Seed: {}", game.seed)];
+    if game.takebacks > 0 {
+        lines.push(format!("Takebacks: {}", game.takebacks));
+    }
+    for player in 0..game.player_count {
+        lines.push(format!("{} = {}", player_tag(player), game.player_names[player as usize]));
+    }
+
+    // The hands for a round are kept with its call, so bids wait until the call turns up
+    let mut round = 1;
+    let mut bids = Vec::new();
+    for event in &game.events {
+        match event {
+            GameEvent::Bet { .. } => bids.extend(event_lines(event, &game.rules)),
+            GameEvent::Call(RoundResolution { hands, .. }) | GameEvent::TimedOut { hands, .. } => {
+                lines.push(String::new());
+                lines.push(format!("Round {}", round));
+                lines.push(hands_line(hands, game.player_count));
+                lines.append(&mut bids);
+                lines.extend(event_lines(event, &game.rules));
+                round += 1;
+            }
+            GameEvent::Eliminated(_) | GameEvent::Won { .. } => lines.extend(event_lines(event, &game.rules)),
+        }
+    }
+
+    // A round still being bid on
+    if game.winner.is_none() && game.phase == Phase::Bidding {
+        let hands = RoundHands {
+            dice_count: game.current_player_dice_count,
+            player_dice: game.player_dice,
+        };
+        lines.push(String::new());
+        lines.push(format!("Round {}", round));
+        lines.push(hands_line(&hands, game.player_count));
+        lines.append(&mut bids);
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Table setup read from the header, before the first round
+struct Header {
+    rules: Rules,
+    seed: u64,
+    takebacks: u32,
+    player_names: Vec<String>,
+}
+
+impl Header {
+    fn new_game(&self) -> Result<Game, String> {
+        if self.player_names.len() < 2 {
+            return Err("A transcript needs at least two players".to_string());
+        }
+        let mut game = Game::with_players(self.player_names.len() as u8, self.rules);
+        for (player, name) in self.player_names.iter().enumerate() {
+            game.player_names[player] = name.clone();
+            game.is_human[player] = false;
+        }
+        game.set_seed(self.seed);
+        game.takebacks = self.takebacks;
+        roll_all_dice(&mut game);
+        Ok(game)
+    }
+}
+
+/// Reads the hands from a `Hands:` line, such as `P1 35512, P2 66421`
+pub fn parse_hands(hands: &str) -> Result<Vec<(u8, Vec<u8>)>, String> {
+    hands
+        .split(',')
+        .map(|hand| {
+            let (tag, faces) = hand.trim().split_once(' ').ok_or(format!("expected `P1 12345`, found `{}`", hand.trim()))?;
+            let player = parse_player_tag(tag).ok_or(format!("unknown player `{}`", tag))?;
+            let dice = faces
+                .chars()
+                .map(|face| face.to_digit(10).filter(|face| (1..=6).contains(face)).map(|face| face as u8))
+                .collect::<Option<_>>()
+                .ok_or(format!("`{}` is not a hand of dice", faces))?;
+            Ok((player, dice))
+        })
+        .collect()
+}
+
+/// Sets the dice from a `Hands:` line, checking every player still in has a full hand
+fn deal_hands(game: &mut Game, hands: &str) -> Result<(), String> {
+    let mut listed = [false; MAX_PLAYERS];
+    for (player, dice) in parse_hands(hands)? {
+        let tag = player_tag(player);
+        if player >= game.player_count {
+            return Err(format!("unknown player `{}`", tag));
+        }
+        if dice.len() != game.current_player_dice_count[player as usize] as usize {
+            return Err(format!(
+                "{} has {} dice, not {}",
+                tag,
+                game.current_player_dice_count[player as usize],
+                dice.len()
+            ));
+        }
+        game.player_dice[player as usize][..dice.len()].copy_from_slice(&dice);
+        listed[player as usize] = true;
+    }
+
+    match (0..game.player_count).find(|&player| game.current_player_dice_count[player as usize] > 0 && !listed[player as usize]) {
+        Some(player) => Err(format!("no hand for {}", player_tag(player))),
+        None => Ok(()),
+    }
+}
+
+/// Reads a move, either a bid like `4x5` or `call`
+pub fn parse_move(move_text: &str) -> Result<PlayerAction, String> {
+    if move_text == "call" {
+        return Ok(PlayerAction {
+            action: Action::Call,
+            bet: None,
+        });
+    }
+    let bet = move_text
+        .split_once('x')
+        .and_then(|(dice_count, face_value)| Some((dice_count.parse().ok()?, face_value.parse().ok()?)))
+        .ok_or(format!("expected a bid like `4x5` or `call`, found `{}`", move_text))?;
+    Ok(PlayerAction {
+        action: Action::Bet,
+        bet: Some(bet),
+    })
+}
+
+/// Reads a bid or call line for the player whose turn it is
+fn parse_action(game: &Game, tag: &str, move_text: &str) -> Result<PlayerAction, String> {
+    let player = parse_player_tag(tag).ok_or(format!("unknown player `{}`", tag))?;
+    if player != game.current_player {
+        return Err(format!("it is {}'s turn, not {}'s", player_tag(game.current_player), tag));
+    }
+
+    parse_move(move_text)
+}
+
+/// Replays a transcript through the engine, returning the table as it was at the deal of
+/// each round and after every move
+pub fn parse(text: &str) -> Result<Vec<Game>, String> {
+    let mut header = Header {
+        rules: Rules::default(),
+        seed: 0,
+        takebacks: 0,
+        player_names: Vec::new(),
+    };
+    let mut game: Option<Game> = None;
+    let mut states = Vec::new();
+    // Lines that have to follow the last call
+    let mut expected: VecDeque<String> = VecDeque::new();
+
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: String| format!("Line {}: {}", line_number + 1, message);
+
+        if let Some(expected_line) = expected.pop_front() {
+            if line != expected_line {
+                return Err(error(format!("expected `{}` from the dice, found `{}`", expected_line, line)));
+            }
+            continue;
+        }
+
+        let Some(current) = &mut game else {
+            // Header lines
+            if let Some(rules) = line.strip_prefix("Rules:") {
+                header.rules = parse_rules(rules.trim()).ok_or(error(format!("invalid rules `{}`", rules.trim())))?;
+            } else if let Some(seed) = line.strip_prefix("Seed:") {
+                header.seed = seed.trim().parse().map_err(|_| error(format!("invalid seed `{}`", seed.trim())))?;
+            } else if let Some(takebacks) = line.strip_prefix("Takebacks:") {
+                header.takebacks = takebacks.trim().parse().map_err(|_| error(format!("invalid takebacks `{}`", takebacks.trim())))?;
+            } else if let Some((tag, name)) = line.split_once(" = ") {
+                let player = parse_player_tag(tag).ok_or(error(format!("unknown player `{}`", tag)))?;
+                if player as usize != header.player_names.len() {
+                    return Err(error(format!("expected {} next", player_tag(header.player_names.len() as u8))));
+                }
+                header.player_names.push(name.trim().to_string());
+            } else if line.starts_with("Round ") {
+                game = Some(header.new_game().map_err(error)?);
+                let current = game.as_mut().unwrap();
+                if line != "Round 1" {
+                    return Err(error(format!("expected `Round 1`, found `{}`", line)));
+                }
+                states.push(current.clone());
+            } else {
+                return Err(error(format!("unrecognised header line `{}`", line)));
+            }
+            continue;
+        };
+
+        if let Some(round) = line.strip_prefix("Round ") {
+            // Deal the next round
+            if current.winner.is_some() {
+                return Err(error("the game is already over".to_string()));
+            }
+            while current.phase != Phase::Bidding {
+                *current = advance_phase(current).map_err(error)?;
+            }
+            if round.parse() != Ok(current.round) {
+                return Err(error(format!("expected `Round {}`, found `{}`", current.round, line)));
+            }
+            states.push(current.clone());
+        } else if let Some(hands) = line.strip_prefix("Hands:") {
+            if current.phase != Phase::Bidding || !current.bets.is_empty() {
+                return Err(error("hands can only be dealt at the start of a round".to_string()));
+            }
+            deal_hands(current, hands.trim()).map_err(error)?;
+            *states.last_mut().unwrap() = current.clone();
+        } else if let Some((tag, move_text)) = line.split_once(": ") {
+            let events_before = current.events.len();
+            let ends_round = if move_text.trim() == "timeout" {
+                if parse_player_tag(tag) != Some(current.current_player) {
+                    return Err(error(format!("it is {}'s turn, not {}'s", player_tag(current.current_player), tag)));
+                }
+                *current = apply_timeout(current, TimeoutAction::LoseDie).map_err(error)?;
+                true
+            } else {
+                let action = parse_action(current, tag, move_text.trim()).map_err(error)?;
+                *current = take_action(current, &action).map_err(error)?;
+                action.action == Action::Call
+            };
+            states.push(current.clone());
+
+            // The call or timeout line itself has been read, the rest has to match
+            if ends_round {
+                expected = current.events[events_before..]
+                    .iter()
+                    .flat_map(|event| event_lines(event, &current.rules))
+                    .skip(1)
+                    .collect();
+            }
+        } else {
+            return Err(error(format!("unrecognised line `{}`", line)));
+        }
+    }
+
+    if let Some(expected_line) = expected.front() {
+        return Err(format!("Transcript ends before `{}`", expected_line));
+    }
+    if states.is_empty() {
+        return Err("Transcript has no rounds".to_string());
+    }
+    Ok(states)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ai::{ai_decide_action, AiDifficulty};
+
+    fn played_game(player_count: u8, rules: Rules, seed: u64, moves: usize) -> Game {
+        let mut game = Game::with_players(player_count, rules);
+        game.set_seed(seed);
+        roll_all_dice(&mut game);
+        for _ in 0..moves {
+            if game.winner.is_some() {
+                break;
+            }
+            game = match game.phase {
+                Phase::Bidding => take_action(&game, &ai_decide_action(&game, AiDifficulty::Normal)).unwrap(),
+                Phase::Revealing | Phase::Dealing => advance_phase(&game).unwrap(),
+            };
+        }
+        game
+    }
+
+    #[test]
+    fn test_finished_game_round_trips() {
+        let rules = Rules {
+            ones_wild: true,
+            starting_dice: 3,
+        };
+        let game = played_game(4, rules, 77, 10_000);
+        assert!(game.winner.is_some());
+
+        let transcript = export(&game);
+        let states = parse(&transcript).unwrap();
+        let replayed = states.last().unwrap();
+        assert_eq!(replayed.winner, game.winner);
+        assert_eq!(replayed.events, game.events);
+        assert_eq!(replayed.eliminations, game.eliminations);
+        assert_eq!(export(replayed), transcript);
+    }
+
+    #[test]
+    fn test_unfinished_game_round_trips() {
+        let mut game = played_game(3, Rules::default(), 5, 12);
+        game.takebacks = 2;
+        let transcript = export(&game);
+        let replayed = parse(&transcript).unwrap().pop().unwrap();
+        assert_eq!(replayed.takebacks, 2);
+        assert_eq!(replayed.player_dice, game.player_dice);
+        assert_eq!(replayed.bets, game.bets);
+        assert_eq!(export(&replayed), transcript);
+    }
+
+    #[test]
+    fn test_parse_reads_the_notation() {
+        let transcript = "\
+# Rusty Dice transcript
+Rules: 2 dice
+Seed: 9
+P1 = Ana
+P2 = Ben
+
+Round 1
+Hands: P1 35, P2 66
+P1: 2x6
+P2: call
+Result: 2 counted, P2 loses a die
+
+Round 2
+Hands: P1 14, P2 2
+P2: 1x4
+P1: call
+Result: 1 counted, P1 loses a die
+";
+        let states = parse(transcript).unwrap();
+        // Each deal and each of the six moves
+        assert_eq!(states.len(), 6);
+        let last = states.last().unwrap();
+        assert_eq!(last.player_names[1], "Ben");
+        assert_eq!(last.current_player_dice_count[..2], [1, 1]);
+        assert_eq!(states[1].player_dice[0][..2], [3, 5]);
+        assert_eq!(states[3].player_dice[1][0], 2);
+    }
+
+    #[test]
+    fn test_parse_rejects_a_wrong_result() {
+        let transcript = "\
+Rules: 2 dice
+P1 = Ana
+P2 = Ben
+Round 1
+Hands: P1 35, P2 66
+P1: 2x6
+P2: call
+Result: 1 counted, P1 loses a die
+";
+        let error = parse(transcript).unwrap_err();
+        assert!(error.starts_with("Line 8: expected `Result: 2 counted, P2 loses a die`"), "{}", error);
+    }
+
+    #[test]
+    fn test_parse_rejects_moves_out_of_turn() {
+        let transcript = "Rules: 2 dice\nP1 = Ana\nP2 = Ben\nRound 1\nHands: P1 35, P2 66\nP2: 2x6\n";
+        assert_eq!(parse(transcript).unwrap_err(), "Line 6: it is P1's turn, not P2's");
+    }
+
+    #[test]
+    fn test_timeouts_round_trip() {
+        let mut game = played_game(3, Rules::default(), 11, 0);
+        let bid = PlayerAction {
+            action: Action::Bet,
+            bet: Some((1, 2)),
+        };
+        game = take_action(&game, &bid).unwrap();
+        game = apply_timeout(&game, TimeoutAction::LoseDie).unwrap();
+        game = advance_phase(&game).unwrap();
+        game = take_action(&game, &bid).unwrap();
+
+        let transcript = export(&game);
+        assert!(transcript.contains("P2: timeout\nResult: P2 ran out of time and loses a die\n"), "{}", transcript);
+        let replayed = parse(&transcript).unwrap().pop().unwrap();
+        assert_eq!(replayed.events, game.events);
+        assert_eq!(replayed.current_player_dice_count, game.current_player_dice_count);
+    }
+
+    #[test]
+    fn test_parse_rejects_short_hands() {
+        let transcript = "Rules: 2 dice\nP1 = Ana\nP2 = Ben\nRound 1\nHands: P1 3, P2 66\n";
+        assert_eq!(parse(transcript).unwrap_err(), "Line 5: P1 has 2 dice, not 1");
+    }
+}
@@ -0,0 +1,289 @@
+// Local player profiles with lifetime statistics, built from each finished game's events
+
+use std::path::Path;
+
+use crate::game::{Game, GameEvent, MAX_PLAYERS};
+
+pub const PROFILES_FILE: &str = "profiles.cfg";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerStats {
+    pub games_played: u32,
+    pub games_won: u32,
+    pub total_finishing_position: u32, // Sum of places, 1 for a win
+    pub bluffs: u32,                    // Bets that turned out to be false
+    pub successful_bluffs: u32,         // False bets someone raised over instead of calling
+    pub calls: u32,
+    pub correct_calls: u32, // Calls on a bet that was false
+    pub dice_lost_calling: u32,
+    pub dice_lost_bidding: u32,
+}
+
+impl PlayerStats {
+    pub fn average_finish(&self) -> Option<f32> {
+        ratio(self.total_finishing_position, self.games_played)
+    }
+
+    pub fn bluff_success_rate(&self) -> Option<f32> {
+        ratio(self.successful_bluffs, self.bluffs)
+    }
+
+    pub fn call_accuracy(&self) -> Option<f32> {
+        ratio(self.correct_calls, self.calls)
+    }
+
+    fn add(&mut self, other: &PlayerStats) {
+        self.games_played += other.games_played;
+        self.games_won += other.games_won;
+        self.total_finishing_position += other.total_finishing_position;
+        self.bluffs += other.bluffs;
+        self.successful_bluffs += other.successful_bluffs;
+        self.calls += other.calls;
+        self.correct_calls += other.correct_calls;
+        self.dice_lost_calling += other.dice_lost_calling;
+        self.dice_lost_bidding += other.dice_lost_bidding;
+    }
+
+    fn fields_mut(&mut self) -> [(&'static str, &mut u32); 9] {
+        [
+            ("games_played", &mut self.games_played),
+            ("games_won", &mut self.games_won),
+            ("total_finishing_position", &mut self.total_finishing_position),
+            ("bluffs", &mut self.bluffs),
+            ("successful_bluffs", &mut self.successful_bluffs),
+            ("calls", &mut self.calls),
+            ("correct_calls", &mut self.correct_calls),
+            ("dice_lost_calling", &mut self.dice_lost_calling),
+            ("dice_lost_bidding", &mut self.dice_lost_bidding),
+        ]
+    }
+}
+
+fn ratio(numerator: u32, denominator: u32) -> Option<f32> {
+    (denominator > 0).then(|| numerator as f32 / denominator as f32)
+}
+
+/// Every profile on this machine, in the order they were first seen
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profiles {
+    pub profiles: Vec<(String, PlayerStats)>,
+}
+
+impl Profiles {
+    /// Loads profiles from the data directory, starting empty if there are none yet
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Could not read {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_config()).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    /// Parses `[name]` headers, each followed by `stat = value` lines
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut profiles = Self::default();
+
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                profiles.profiles.push((name.to_string(), PlayerStats::default()));
+                continue;
+            }
+
+            let Some((_, stats)) = profiles.profiles.last_mut() else {
+                return Err(format!("Line {}: expected a `[name]` header", line_number + 1));
+            };
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("Line {}: expected `stat = value`", line_number + 1));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let Some((_, field)) = stats.fields_mut().into_iter().find(|(name, _)| *name == key) else {
+                return Err(format!("Line {}: unknown stat `{}`", line_number + 1, key));
+            };
+            *field = value
+                .parse()
+                .map_err(|_| format!("Line {}: invalid value `{}` for `{}`", line_number + 1, value, key))?;
+        }
+
+        Ok(profiles)
+    }
+
+    pub fn to_config(&self) -> String {
+        let mut lines = vec!["# Rusty Dice player profiles".to_string()];
+        for (name, stats) in &self.profiles {
+            lines.push(format!("[{}]", name));
+            let mut stats = stats.clone();
+            for (key, value) in stats.fields_mut() {
+                lines.push(format!("{} = {}", key, value));
+            }
+        }
+        lines.join("\n") + "\n"
+    }
+
+    fn stats_mut(&mut self, name: &str) -> &mut PlayerStats {
+        let index = match self.profiles.iter().position(|(profile, _)| profile == name) {
+            Some(index) => index,
+            None => {
+                self.profiles.push((name.to_string(), PlayerStats::default()));
+                self.profiles.len() - 1
+            }
+        };
+        &mut self.profiles[index].1
+    }
+
+    /// Adds a finished game to the profiles of the human seats, which are matched by name
+    pub fn record_game(&mut self, game: &Game) {
+        let game_stats = game_stats(game);
+        for (player, stats) in game_stats.iter().enumerate().take(game.player_count as usize) {
+            if game.is_human[player] {
+                self.stats_mut(&game.player_names[player]).add(stats);
+            }
+        }
+    }
+}
+
+/// Each seat's stats for one finished game, worked out from its events
+fn game_stats(game: &Game) -> [PlayerStats; MAX_PLAYERS] {
+    let mut stats: [PlayerStats; MAX_PLAYERS] = Default::default();
+    let mut round_bets = Vec::new();
+
+    for event in &game.events {
+        match event {
+            GameEvent::Bet { player, dice_count, face_value } => round_bets.push((*player, *dice_count, *face_value)),
+            GameEvent::Call(resolution) => {
+                // Every bet in the round can be checked against the hands once they are shown
+                for (i, &(player, dice_count, face_value)) in round_bets.iter().enumerate() {
+                    let (matching, _) = resolution.hands.count_matching(face_value, &game.rules);
+                    if matching < dice_count {
+                        let bettor = &mut stats[player as usize];
+                        bettor.bluffs += 1;
+                        if i + 1 < round_bets.len() {
+                            bettor.successful_bluffs += 1;
+                        }
+                    }
+                }
+
+                let caller = &mut stats[resolution.caller as usize];
+                caller.calls += 1;
+                if resolution.actual_count < resolution.bet.1 {
+                    caller.correct_calls += 1;
+                }
+
+                let loser = &mut stats[resolution.losing_player as usize];
+                if resolution.losing_player == resolution.caller {
+                    loser.dice_lost_calling += 1;
+                } else {
+                    loser.dice_lost_bidding += 1;
+                }
+                round_bets.clear();
+            }
+            // A round lost to the clock has no call to check the bets against
+            GameEvent::TimedOut { .. } => round_bets.clear(),
+            GameEvent::Eliminated(_) => {}
+            GameEvent::Won { player } => stats[*player as usize].games_won += 1,
+        }
+    }
+
+    for (place, &player) in game.finishing_order().iter().enumerate() {
+        stats[player as usize].games_played = 1;
+        stats[player as usize].total_finishing_position = place as u32 + 1;
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::{take_action, Action, PlayerAction, Rules};
+
+    fn stats<'a>(profiles: &'a Profiles, name: &str) -> Option<&'a PlayerStats> {
+        profiles.profiles.iter().find(|(profile, _)| profile == name).map(|(_, stats)| stats)
+    }
+
+    fn bet(game: &Game, dice_count: u8, face_value: u8) -> Game {
+        let action = PlayerAction {
+            action: Action::Bet,
+            bet: Some((dice_count, face_value)),
+        };
+        take_action(game, &action).unwrap()
+    }
+
+    fn call(game: &Game) -> Game {
+        let action = PlayerAction {
+            action: Action::Call,
+            bet: None,
+        };
+        take_action(game, &action).unwrap()
+    }
+
+    /// Player 0 bluffs twice against player 1, who calls the second bluff and wins
+    fn create_finished_game() -> Game {
+        let mut game = Game::with_players(2, Rules { ones_wild: false, starting_dice: 1 });
+        game.player_names[0] = "Ana".to_string();
+        game.player_names[1] = "Ben".to_string();
+        game.is_human = [true; MAX_PLAYERS];
+        game.player_dice[0] = [2, 0, 0, 0, 0];
+        game.player_dice[1] = [3, 0, 0, 0, 0];
+
+        game = bet(&game, 1, 4); // Bluff, raised over
+        game = bet(&game, 1, 5); // Player 1 bluffs too
+        game = bet(&game, 2, 2); // Bluff, called
+        call(&game)
+    }
+
+    #[test]
+    fn test_record_game_counts_bluffs_calls_and_places() {
+        let mut profiles = Profiles::default();
+        profiles.record_game(&create_finished_game());
+
+        let ana = stats(&profiles, "Ana").unwrap();
+        assert_eq!(ana.games_played, 1);
+        assert_eq!(ana.games_won, 0);
+        assert_eq!(ana.average_finish(), Some(2.0));
+        assert_eq!((ana.bluffs, ana.successful_bluffs), (2, 1));
+        assert_eq!(ana.dice_lost_bidding, 1);
+        assert_eq!(ana.call_accuracy(), None);
+
+        let ben = stats(&profiles, "Ben").unwrap();
+        assert_eq!(ben.games_won, 1);
+        assert_eq!(ben.average_finish(), Some(1.0));
+        assert_eq!(ben.bluff_success_rate(), Some(1.0));
+        assert_eq!(ben.call_accuracy(), Some(1.0));
+        assert_eq!(ben.dice_lost_calling, 0);
+    }
+
+    #[test]
+    fn test_record_game_skips_ai_seats_and_adds_up() {
+        let mut game = create_finished_game();
+        game.is_human[1] = false;
+
+        let mut profiles = Profiles::default();
+        profiles.record_game(&game);
+        profiles.record_game(&game);
+        assert_eq!(stats(&profiles, "Ben"), None);
+        assert_eq!(stats(&profiles, "Ana").unwrap().games_played, 2);
+        assert_eq!(stats(&profiles, "Ana").unwrap().total_finishing_position, 4);
+    }
+
+    #[test]
+    fn test_saved_profiles_parse_back() {
+        let mut profiles = Profiles::default();
+        profiles.record_game(&create_finished_game());
+        assert_eq!(Profiles::parse(&profiles.to_config()).unwrap(), profiles);
+    }
+
+    #[test]
+    fn test_parse_stat_without_header_returns_error() {
+        assert_eq!(Profiles::parse("calls = 3").unwrap_err(), "Line 1: expected a `[name]` header");
+        assert_eq!(Profiles::parse("[Ana]\nluck = 3").unwrap_err(), "Line 2: unknown stat `luck`");
+    }
+}
@@ -0,0 +1,219 @@
+// Elo-style ratings for free-for-all games, shared by human profiles and AI strategies
+
+use std::path::Path;
+
+use crate::game::Game;
+use crate::settings::SeatKind;
+
+pub const RATINGS_FILE: &str = "ratings.cfg";
+
+const STARTING_RATING: f32 = 1500.0;
+const K_FACTOR: f32 = 32.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    pub rating: f32,
+    pub games: u32,
+}
+
+/// Everyone who has played a rated game, humans by profile name and AIs by strategy
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ratings {
+    pub ratings: Vec<(String, Rating)>,
+}
+
+/// The leaderboard name for a seat. AI strategies are tagged with the build they came from,
+/// so different versions of the same strategy are rated separately.
+pub fn participant_name(seat: SeatKind, player_name: &str) -> String {
+    match seat {
+        SeatKind::Human => player_name.to_string(),
+        SeatKind::Ai(difficulty) => format!("AI {} v{}", difficulty.name(), env!("CARGO_PKG_VERSION")),
+    }
+}
+
+impl Ratings {
+    /// Loads ratings from the data directory, starting empty if there are none yet
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Could not read {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_config()).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    /// Parses `name = rating, games` lines
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut ratings = Self::default();
+
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parsed = line.rsplit_once('=').and_then(|(name, value)| {
+                let (rating, games) = value.split_once(',')?;
+                let rating = Rating {
+                    rating: rating.trim().parse().ok()?,
+                    games: games.trim().parse().ok()?,
+                };
+                Some((name.trim().to_string(), rating))
+            });
+            match parsed {
+                Some(entry) => ratings.ratings.push(entry),
+                None => return Err(format!("Line {}: expected `name = rating, games`", line_number + 1)),
+            }
+        }
+
+        Ok(ratings)
+    }
+
+    pub fn to_config(&self) -> String {
+        let mut lines = vec!["# Rusty Dice ratings: name = rating, games".to_string()];
+        for (name, rating) in &self.ratings {
+            lines.push(format!("{} = {:.1}, {}", name, rating.rating, rating.games));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    pub fn rating(&self, name: &str) -> f32 {
+        self.ratings
+            .iter()
+            .find(|(rated, _)| rated == name)
+            .map_or(STARTING_RATING, |(_, rating)| rating.rating)
+    }
+
+    fn entry(&mut self, name: &str) -> &mut Rating {
+        let index = match self.ratings.iter().position(|(rated, _)| rated == name) {
+            Some(index) => index,
+            None => {
+                self.ratings.push((
+                    name.to_string(),
+                    Rating {
+                        rating: STARTING_RATING,
+                        games: 0,
+                    },
+                ));
+                self.ratings.len() - 1
+            }
+        };
+        &mut self.ratings[index].1
+    }
+
+    /// Updates ratings from a finishing order, best first. Each finisher is scored as having
+    /// beaten everyone below them and lost to everyone above, and the pairwise Elo changes are
+    /// scaled down by the field size so a big table moves ratings about as much as a duel.
+    /// Seats sharing a name (several copies of one AI) don't play each other; their changes
+    /// are added together and count as one game.
+    pub fn record_result(&mut self, finishing_order: &[String]) {
+        let field_size = finishing_order.len();
+        if field_size < 2 {
+            return;
+        }
+
+        let before: Vec<f32> = finishing_order.iter().map(|name| self.rating(name)).collect();
+        let mut changes = vec![0.0; field_size];
+        for i in 0..field_size {
+            for j in 0..field_size {
+                if finishing_order[i] == finishing_order[j] {
+                    continue;
+                }
+                let expected = 1.0 / (1.0 + 10f32.powf((before[j] - before[i]) / 400.0));
+                let score = if i < j { 1.0 } else { 0.0 };
+                changes[i] += K_FACTOR / (field_size - 1) as f32 * (score - expected);
+            }
+        }
+
+        let mut counted: Vec<&String> = Vec::new();
+        for (name, change) in finishing_order.iter().zip(changes) {
+            let entry = self.entry(name);
+            entry.rating += change;
+            if !counted.contains(&name) {
+                entry.games += 1;
+                counted.push(name);
+            }
+        }
+    }
+
+    /// Rates a finished game, naming each seat with `participant_name`
+    pub fn record_game(&mut self, game: &Game, seats: &[SeatKind]) {
+        let finishing_order: Vec<String> = game
+            .finishing_order()
+            .into_iter()
+            .map(|player| participant_name(seats[player as usize], &game.player_names[player as usize]))
+            .collect();
+        self.record_result(&finishing_order);
+    }
+
+    /// Everyone rated, highest first
+    pub fn leaderboard(&self) -> Vec<(String, Rating)> {
+        let mut leaderboard = self.ratings.clone();
+        leaderboard.sort_by(|(_, a), (_, b)| b.rating.total_cmp(&a.rating));
+        leaderboard
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ai::AiDifficulty;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_finishing_order_spreads_ratings() {
+        let mut ratings = Ratings::default();
+        ratings.record_result(&names(&["Ana", "Ben", "Cai"]));
+
+        let (ana, ben, cai) = (ratings.rating("Ana"), ratings.rating("Ben"), ratings.rating("Cai"));
+        assert!(ana > ben && ben > cai);
+        assert!((ben - STARTING_RATING).abs() < 0.01); // Beat one, lost to one
+        assert!((ana + ben + cai - 3.0 * STARTING_RATING).abs() < 0.01); // Points only change hands
+    }
+
+    #[test]
+    fn test_two_player_game_matches_elo() {
+        let mut ratings = Ratings::default();
+        ratings.record_result(&names(&["Ana", "Ben"]));
+        assert!((ratings.rating("Ana") - (STARTING_RATING + K_FACTOR / 2.0)).abs() < 0.01);
+        assert_eq!(ratings.leaderboard()[0].0, "Ana");
+        assert_eq!(ratings.leaderboard()[1].1.games, 1);
+    }
+
+    #[test]
+    fn test_upset_moves_ratings_more() {
+        let mut ratings = Ratings::default();
+        ratings.entry("Ana").rating = 1700.0;
+        ratings.record_result(&names(&["Ben", "Ana"]));
+        assert!(ratings.rating("Ben") - STARTING_RATING > K_FACTOR / 2.0);
+    }
+
+    #[test]
+    fn test_copies_of_one_ai_share_a_rating() {
+        let ai = participant_name(SeatKind::Ai(AiDifficulty::Hard), "Player 2");
+        let mut ratings = Ratings::default();
+        ratings.record_result(&[ai.clone(), ai.clone(), "Ana".to_string()]);
+        assert_eq!(ratings.ratings.len(), 2);
+        assert!((ratings.rating(&ai) - (STARTING_RATING + K_FACTOR / 2.0)).abs() < 0.01);
+        assert!((ratings.rating("Ana") - (STARTING_RATING - K_FACTOR / 2.0)).abs() < 0.01);
+        assert!(ratings.ratings.iter().all(|(_, rating)| rating.games == 1));
+    }
+
+    #[test]
+    fn test_saved_ratings_parse_back() {
+        let mut ratings = Ratings::default();
+        ratings.record_result(&names(&["Ana = the best", "Ben"]));
+        let parsed = Ratings::parse(&ratings.to_config()).unwrap();
+        assert_eq!(parsed.ratings.len(), 2);
+        assert_eq!(parsed.ratings[0].0, "Ana = the best");
+        assert!((parsed.rating("Ben") - ratings.rating("Ben")).abs() < 0.1);
+        assert!(Ratings::parse("Ana = lots").is_err());
+    }
+}
@@ -1,10 +1,85 @@
 use macroquad::prelude::*;
-use crate::game::{Game, Action, PlayerAction};
-use crate::dice::draw_dice_dots;
+use crate::game::{Game, Action, Phase, PlayerAction, RoundHands, RoundResolution, is_legal_bet, legal_raises, minimum_raise};
+use crate::ai::hint;
+use crate::dice::{draw_die, DiceStyle};
+use crate::history::{history_lines, round_history, visible_lines, HistoryLine, RoundOutcome};
+use crate::i18n::{tr, tr_args, tr_count};
+use crate::animation::{Animation, AnimationStage};
+use crate::keybindings::{Command, KeyBindings};
+use crate::layout::{Layout, PICKER_VISIBLE_ROWS};
+use crate::skins::{load_custom_faces, DiceSkin, DICE_SKINS_DIR};
+use crate::theme::{Palette, Theme, SEAT_COLORS};
+use std::collections::HashMap;
+use std::path::Path;
+use macroquad::rand::gen_range;
+
+/// Who is looking at the table, which decides whose dice are drawn face-up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Viewer {
+    Player(u8),
+    // Hot-seat table between human turns: no hand is face-up and nobody can act
+    Hidden,
+    // Sees only what every player can see
+    Spectator,
+    // Sees every hand, but only once the round it belongs to is over
+    OmniscientSpectator,
+    // Steps through a transcript with every hand face-up
+    Replay,
+}
+
+impl Viewer {
+    /// The seat this viewer plays from, if any
+    pub fn seat(&self) -> Option<u8> {
+        match self {
+            Viewer::Player(player) => Some(*player),
+            _ => None,
+        }
+    }
+}
+
+/// The control that keyboard input acts on, in Tab order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Call,
+    Bet,
+    BidPicker,
+    MinimumRaise,
+}
+
+impl Focus {
+    fn next(self) -> Self {
+        match self {
+            Focus::Call => Focus::Bet,
+            Focus::Bet => Focus::BidPicker,
+            Focus::BidPicker => Focus::MinimumRaise,
+            Focus::MinimumRaise => Focus::Call,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            Focus::Call => Focus::MinimumRaise,
+            Focus::Bet => Focus::Call,
+            Focus::BidPicker => Focus::Bet,
+            Focus::MinimumRaise => Focus::BidPicker,
+        }
+    }
+}
 
 pub struct RenderState {
     pub selected_dice_count: u8,
     pub selected_face_value: u8,
+    pub focus: Focus,
+    pub picker_scroll: usize, // First bid picker row on screen
+    history_scroll: usize, // History panel lines scrolled back from the newest
+    expanded_rounds: Vec<u32>, // Finished rounds opened in the history panel
+    pub palette: Palette,
+    pub hints_allowed: bool, // Off in rated games
+    pub show_hints: bool,
+    pub turn_time_left: Option<(u8, f32)>, // Player on the clock and the share of their turn left
+    pub dice_skins: Vec<DiceSkin>, // Each player's dice
+    custom_faces: HashMap<String, Option<[Texture2D; 6]>>, // Custom skin pictures, None if they couldn't be loaded
+    pub notice: Option<String>, // A short message under the header, such as why a key did nothing
 }
 
 impl RenderState {
@@ -12,262 +87,887 @@ impl RenderState {
         Self {
             selected_dice_count: 1,
             selected_face_value: 1,
+            focus: Focus::Bet,
+            picker_scroll: 0,
+            history_scroll: 0,
+            expanded_rounds: Vec::new(),
+            palette: Theme::Light.palette(false),
+            hints_allowed: false,
+            show_hints: false,
+            turn_time_left: None,
+            dice_skins: Vec::new(),
+            custom_faces: HashMap::new(),
+            notice: None,
+        }
+    }
+
+    /// Loads the pictures for any custom dice skins at the table the first time they're drawn
+    fn load_custom_faces(&mut self) {
+        for skin in &self.dice_skins {
+            if let DiceSkin::Custom(name) = skin {
+                if !self.custom_faces.contains_key(name) {
+                    let faces = load_custom_faces(Path::new(DICE_SKINS_DIR), name)
+                        .map_err(|e| println!("Dice skin error: {}", e))
+                        .ok();
+                    self.custom_faces.insert(name.clone(), faces);
+                }
+            }
+        }
+    }
+
+    /// How a player's dice are drawn, from their skin and the theme
+    fn dice_style(&self, player: usize) -> DiceStyle {
+        let skin = self.dice_skins.get(player).unwrap_or(&DiceSkin::Theme);
+        let faces = match skin {
+            DiceSkin::Custom(name) => self.custom_faces.get(name).and_then(Option::as_ref),
+            _ => None,
+        };
+        skin.style(&self.palette, faces)
+    }
+
+    pub fn selected_bet(&self) -> (u8, u8) {
+        (self.selected_dice_count, self.selected_face_value)
+    }
+
+    fn select(&mut self, bet: (u8, u8)) {
+        (self.selected_dice_count, self.selected_face_value) = bet;
+    }
+
+    /// Selects the lowest bet that beats the last one, if there is one
+    pub fn select_minimum_raise(&mut self, game: &Game) {
+        if let Some(bet) = minimum_raise(game) {
+            self.select(bet);
+        }
+        self.picker_scroll = 0;
+    }
+
+    /// Moves the selection through the legal raises, lowest first
+    fn step_selection(&mut self, game: &Game, step: i32) {
+        let raises = legal_raises(game);
+        let Some(index) = raises.iter().position(|&bet| bet == self.selected_bet()) else {
+            self.select_minimum_raise(game);
+            return;
+        };
+        let new_index = (index as i32 + step).clamp(0, raises.len() as i32 - 1);
+        self.select(raises[new_index as usize]);
+    }
+
+    /// Steps the selected dice count, staying within the dice in play
+    fn step_dice_count(&mut self, game: &Game, step: i8) {
+        let max_dice_count = game.total_dice().max(1) as i8;
+        self.selected_dice_count = (self.selected_dice_count as i8 + step).clamp(1, max_dice_count) as u8;
+    }
+
+    /// Steps the selected face value, wrapping within 1-6
+    fn step_face_value(&mut self, step: i8) {
+        self.selected_face_value = ((self.selected_face_value as i8 - 1 + step).rem_euclid(6) + 1) as u8;
+    }
+
+    /// Scrolls the bid picker so the selected bet's row is on screen
+    fn scroll_to_selection(&mut self, game: &Game) {
+        let rows = picker_rows(game);
+        if let Some(row) = rows.iter().position(|(dice_count, _)| *dice_count == self.selected_dice_count) {
+            if row < self.picker_scroll {
+                self.picker_scroll = row;
+            } else if row >= self.picker_scroll + PICKER_VISIBLE_ROWS {
+                self.picker_scroll = row + 1 - PICKER_VISIBLE_ROWS;
+            }
+        }
+    }
+}
+
+/// Legal raises grouped by dice count, as (dice_count, faces) rows
+fn picker_rows(game: &Game) -> Vec<(u8, Vec<u8>)> {
+    let mut rows: Vec<(u8, Vec<u8>)> = Vec::new();
+    for (dice_count, face_value) in legal_raises(game) {
+        match rows.last_mut() {
+            Some((row_count, faces)) if *row_count == dice_count => faces.push(face_value),
+            _ => rows.push((dice_count, vec![face_value])),
         }
     }
+    rows
 }
 
-pub fn render_game(game: &Game, render_state: &mut RenderState, dice_revealed: bool) -> Option<PlayerAction> {
-    clear_background(WHITE);
+pub fn render_game(game: &Game, render_state: &mut RenderState, key_bindings: &KeyBindings, viewer: Viewer, animation: Option<&Animation>) -> Option<PlayerAction> {
+    clear_background(render_state.palette.background);
 
     // Check if there's a winner
     if let Some(winner_id) = game.winner {
-        render_winner_screen(game, winner_id);
+        render_winner_screen(game, winner_id, &render_state.palette);
         return None;
     }
 
-    // Handle mouse input for UI controls
-    if is_mouse_button_pressed(MouseButton::Left) {
+    let layout = Layout::new(screen_width(), screen_height(), game.player_count);
+    render_state.load_custom_faces();
+    handle_history_input(game, render_state, &layout);
+
+    // Handle mouse input for UI controls (only a seated player can act)
+    if viewer.seat().is_some() && is_mouse_button_pressed(MouseButton::Left) {
         let (mouse_x, mouse_y) = mouse_position();
         let mouse_pos = Vec2::new(mouse_x, mouse_y);
         
         // Call button (only enabled when bets exist)
-        let call_button_rect = Rect::new(50.0, screen_height() - 100.0, 120.0, 40.0);
-        if call_button_rect.contains(mouse_pos) && !game.bets.is_empty() {
+        if layout.call_button().contains(mouse_pos) && !game.bets.is_empty() {
             return Some(PlayerAction {
                 action: Action::Call,
                 bet: None,
             });
         }
         
-        // Bet button
-        let bet_button_rect = Rect::new(200.0, screen_height() - 100.0, 120.0, 40.0);
-        if bet_button_rect.contains(mouse_pos) {
+        // Bet button (only enabled when the selected bet is a legal raise)
+        if layout.bet_button().contains(mouse_pos) && is_legal_bet(game, render_state.selected_bet()) {
             return Some(PlayerAction {
                 action: Action::Bet,
-                bet: Some((render_state.selected_dice_count, render_state.selected_face_value)),
+                bet: Some(render_state.selected_bet()),
             });
         }
         
-        // Dice count dropdown
-        let dice_dropdown_rect = Rect::new(350.0, screen_height() - 100.0, 80.0, 40.0);
-        if dice_dropdown_rect.contains(mouse_pos) {
-            render_state.selected_dice_count = (render_state.selected_dice_count % 20) + 1; // Cycle through 1-20
+        // Bid picker
+        let rows = picker_rows(game);
+        for (visible_row, (dice_count, faces)) in rows.iter().skip(render_state.picker_scroll).take(PICKER_VISIBLE_ROWS).enumerate() {
+            for &face_value in faces {
+                if layout.picker_icon(visible_row, face_value).contains(mouse_pos) {
+                    render_state.select((*dice_count, face_value));
+                }
+            }
         }
-        
-        // Face value dropdown
-        let face_dropdown_rect = Rect::new(450.0, screen_height() - 100.0, 80.0, 40.0);
-        if face_dropdown_rect.contains(mouse_pos) {
-            render_state.selected_face_value = (render_state.selected_face_value % 6) + 1; // Cycle through 1-6
+
+        // Minimum raise shortcut
+        if layout.minimum_raise_button().contains(mouse_pos) {
+            render_state.select_minimum_raise(game);
         }
     }
 
-    render_game_ui(game, render_state, dice_revealed);
+    // The scroll wheel steps through the legal raises while over the bid picker
+    if viewer.seat().is_some() {
+        let (mouse_x, mouse_y) = mouse_position();
+        let (_, wheel_y) = mouse_wheel();
+        if wheel_y != 0.0 && layout.bid_picker().contains(Vec2::new(mouse_x, mouse_y)) {
+            render_state.step_selection(game, if wheel_y > 0.0 { 1 } else { -1 });
+            render_state.scroll_to_selection(game);
+        }
+    }
+
+    // Handle keyboard input for the same controls
+    if viewer.seat().is_some() {
+        if let Some(action) = handle_keyboard(game, render_state, key_bindings) {
+            return Some(action);
+        }
+    }
+
+    render_game_ui(game, render_state, &layout, viewer, animation);
     None
 }
 
-fn render_winner_screen(game: &Game, winner_id: u8) {
-    // Display winner screen
-    draw_text(
-        "Game Over!",
-        screen_width() / 2.0 - 80.0,
+/// The wheel scrolls the history panel, and clicking a finished round's heading opens or
+/// closes it. Anyone watching can do this, not only the player on turn.
+fn handle_history_input(game: &Game, render_state: &mut RenderState, layout: &Layout) {
+    let mouse = Vec2::from(mouse_position());
+    if !layout.history_panel.contains(mouse) {
+        return;
+    }
+    let rounds = round_history(&game.events);
+    let lines = history_lines(&rounds, &render_state.expanded_rounds);
+
+    let (_, wheel_y) = mouse_wheel();
+    if wheel_y > 0.0 {
+        render_state.history_scroll += 1;
+    } else if wheel_y < 0.0 {
+        render_state.history_scroll = render_state.history_scroll.saturating_sub(1);
+    }
+    let (visible, scroll) = visible_lines(lines.len(), history_rows(layout), render_state.history_scroll);
+    render_state.history_scroll = scroll;
+
+    if !is_mouse_button_pressed(MouseButton::Left) {
+        return;
+    }
+    let line_height = layout.font(20.0);
+    let row = (mouse.y - layout.history_panel.y - 1.2 * line_height) / line_height;
+    if row < 0.0 {
+        return;
+    }
+    if let Some(HistoryLine::Round { index, .. }) = lines[visible].get(row as usize) {
+        let round = &rounds[*index];
+        if round.outcome.is_some() {
+            match render_state.expanded_rounds.iter().position(|&number| number == round.number) {
+                Some(position) => {
+                    render_state.expanded_rounds.remove(position);
+                }
+                None => render_state.expanded_rounds.push(round.number),
+            }
+        }
+    }
+}
+
+/// How many lines fit in the history panel below its title
+fn history_rows(layout: &Layout) -> usize {
+    ((layout.history_panel.h / layout.font(20.0)) as usize).saturating_sub(2)
+}
+
+fn handle_keyboard(game: &Game, render_state: &mut RenderState, key_bindings: &KeyBindings) -> Option<PlayerAction> {
+    let call = PlayerAction {
+        action: Action::Call,
+        bet: None,
+    };
+
+    for command in key_bindings.pressed_commands() {
+        match command {
+            Command::IncreaseQuantity => render_state.step_dice_count(game, 1),
+            Command::DecreaseQuantity => render_state.step_dice_count(game, -1),
+            Command::NextFace => render_state.step_face_value(1),
+            Command::PreviousFace => render_state.step_face_value(-1),
+            Command::SelectFace(face_value) => render_state.selected_face_value = face_value,
+            Command::MinimumRaise => render_state.select_minimum_raise(game),
+            Command::NextFocus => render_state.focus = render_state.focus.next(),
+            Command::PreviousFocus => render_state.focus = render_state.focus.previous(),
+            Command::ToggleHints => render_state.show_hints = render_state.hints_allowed && !render_state.show_hints,
+            Command::Call if !game.bets.is_empty() => return Some(call),
+            Command::Activate if render_state.focus == Focus::Call => {
+                if !game.bets.is_empty() {
+                    return Some(call);
+                }
+            }
+            Command::Activate if render_state.focus == Focus::MinimumRaise => {
+                render_state.select_minimum_raise(game);
+            }
+            // Pressing Enter on the bet button or the bid picker places the selected bet
+            Command::Activate => {
+                if is_legal_bet(game, render_state.selected_bet()) {
+                    return Some(PlayerAction {
+                        action: Action::Bet,
+                        bet: Some(render_state.selected_bet()),
+                    });
+                }
+            }
+            // These act on the whole session, so they are handled outside the table
+            Command::Call | Command::Undo | Command::FastForward | Command::SkipAnimation => {}
+        }
+        render_state.scroll_to_selection(game);
+    }
+    None
+}
+
+fn render_winner_screen(game: &Game, winner_id: u8, palette: &Palette) {
+    // Display winner screen. Translations vary in length, so the text is centred.
+    draw_centered_text(
+        &tr("winner.game_over"),
+        screen_width() / 2.0,
         screen_height() / 2.0 - 100.0,
         50.0,
-        BLACK,
+        palette.text,
     );
     
-    draw_text(
-        &format!("Winner: {}", game.player_names[winner_id as usize]),
-        screen_width() / 2.0 - 120.0,
+    draw_centered_text(
+        &tr_args("winner.winner", &[("name", &game.player_names[winner_id as usize])]),
+        screen_width() / 2.0,
         screen_height() / 2.0 - 40.0,
         30.0,
-        GREEN,
+        palette.positive,
     );
+    if game.takebacks > 0 {
+        draw_centered_text(
+            &tr_args("winner.takebacks", &[("count", &game.takebacks)]),
+            screen_width() / 2.0,
+            screen_height() / 2.0 - 10.0,
+            20.0,
+            palette.muted_text,
+        );
+    }
     
     // Restart button
     let restart_button_rect = Rect::new(screen_width() / 2.0 - 80.0, screen_height() / 2.0 + 20.0, 160.0, 50.0);
-    draw_rectangle(restart_button_rect.x, restart_button_rect.y, restart_button_rect.w, restart_button_rect.h, BLUE);
-    draw_text(
-        "Restart Game",
-        screen_width() / 2.0 - 60.0,
+    draw_rectangle(restart_button_rect.x, restart_button_rect.y, restart_button_rect.w, restart_button_rect.h, palette.button);
+    draw_centered_text(
+        &tr("winner.restart"),
+        screen_width() / 2.0,
         screen_height() / 2.0 + 35.0,
         24.0,
-        WHITE,
+        palette.button_text,
     );
+
+    // Full standings beside the buttons, winner first
+    let standings_x = screen_width() / 2.0 + 120.0;
+    draw_text(&tr("winner.standings"), standings_x, screen_height() / 2.0 + 35.0, 24.0, palette.text);
+    for (place, player) in game.finishing_order().into_iter().enumerate() {
+        let name = &game.player_names[player as usize];
+        let line = match game.elimination_round(player) {
+            Some(round) => tr_args("winner.place_out", &[("place", &(place + 1)), ("name", name), ("round", &round)]),
+            None => tr_args("winner.place", &[("place", &(place + 1)), ("name", name)]),
+        };
+        draw_text(&line, standings_x, screen_height() / 2.0 + 65.0 + place as f32 * 26.0, 20.0, palette.muted_text);
+    }
+
+    // Main menu, stats and transcript buttons
+    for (row, label) in [(1.0, "common.main_menu"), (2.0, "common.stats"), (3.0, "winner.save_game")] {
+        let button_rect = winner_button_rect(row);
+        draw_rectangle(button_rect.x, button_rect.y, button_rect.w, button_rect.h, palette.disabled);
+        draw_centered_text(&tr(label), button_rect.center().x, button_rect.y + 35.0, 24.0, palette.button_text);
+    }
+}
+
+/// Buttons below "Restart Game" on the winner screen, one per row
+fn winner_button_rect(row: f32) -> Rect {
+    Rect::new(screen_width() / 2.0 - 80.0, screen_height() / 2.0 + 20.0 + row * 70.0, 160.0, 50.0)
 }
 
-fn render_game_ui(game: &Game, render_state: &RenderState, dice_revealed: bool) {
+/// Privacy screen shown between hot-seat turns so the next player can take the device
+pub fn render_handoff_screen(game: &Game, next_player: u8, palette: &Palette) {
+    clear_background(palette.background);
+
+    draw_centered_text(
+        &tr_args("handoff.pass_to", &[("name", &game.player_names[next_player as usize])]),
+        screen_width() / 2.0,
+        screen_height() / 2.0 - 100.0,
+        50.0,
+        palette.text,
+    );
+
+    draw_centered_text(
+        &tr("handoff.look_away"),
+        screen_width() / 2.0,
+        screen_height() / 2.0 - 40.0,
+        24.0,
+        palette.muted_text,
+    );
+
+    // Ready button
+    let ready_button_rect = Rect::new(screen_width() / 2.0 - 80.0, screen_height() / 2.0 + 20.0, 160.0, 50.0);
+    draw_rectangle(ready_button_rect.x, ready_button_rect.y, ready_button_rect.w, ready_button_rect.h, palette.button);
+    draw_centered_text(
+        &tr("handoff.ready"),
+        screen_width() / 2.0,
+        screen_height() / 2.0 + 35.0,
+        24.0,
+        palette.button_text,
+    );
+}
+
+fn render_game_ui(game: &Game, render_state: &RenderState, layout: &Layout, viewer: Viewer, animation: Option<&Animation>) {
+    // While a reveal plays, the table shows the hands that settled the bet
+    let reveal = animation.filter(|animation| animation.is_revealing()).and_then(|animation| animation.reveal.as_ref());
+    let stage = animation.map_or(AnimationStage::Done, |animation| animation.stage());
+    let dice_revealed = reveal.is_some();
+    let palette = &render_state.palette;
+
     // Draw title
-    draw_text(
-        "Rusty Dice - 4 Players",
-        screen_width() / 2.0 - 120.0,
-        30.0,
-        40.0,
-        BLACK,
+    draw_centered_text(
+        &tr_count("game.title", game.player_count.into(), &[]),
+        layout.header.center().x,
+        layout.header.y + layout.font(34.0),
+        layout.font(40.0),
+        palette.text,
     );
 
     // Draw instructions
-    draw_text(
-        "Click to bet or call!",
-        screen_width() / 2.0 - 100.0,
-        70.0,
-        20.0,
-        DARKGRAY,
+    let instructions = match viewer {
+        Viewer::Player(_) => "game.instructions.player",
+        Viewer::Hidden => "game.instructions.hidden",
+        Viewer::Spectator => "game.instructions.spectator",
+        Viewer::OmniscientSpectator => "game.instructions.omniscient",
+        Viewer::Replay => "game.instructions.replay",
+    };
+    draw_centered_text(
+        &tr(instructions),
+        layout.header.center().x,
+        layout.header.y + layout.font(70.0),
+        layout.font(20.0),
+        palette.muted_text,
     );
     
     // Draw dice visibility status
-    let visibility_text = if dice_revealed {
-        "Dice Revealed - All players can see all dice"
+    let visibility_text = tr(if dice_revealed {
+        "game.visibility.revealed"
+    } else if viewer == Viewer::Replay {
+        "game.visibility.replay"
+    } else if viewer.seat().is_none() {
+        "game.visibility.spectator"
     } else {
-        "Dice Hidden - Only you can see your own dice"
-    };
-    let visibility_color = if dice_revealed { GREEN } else { ORANGE };
-    draw_text(
-        visibility_text,
-        screen_width() / 2.0 - 150.0,
-        95.0,
-        16.0,
-        visibility_color,
+        "game.visibility.player"
+    });
+    let visibility_color = if dice_revealed { palette.positive } else { palette.warning };
+    let visibility_y = layout.header.y + layout.font(95.0);
+    draw_centered_text(&visibility_text, layout.header.center().x, visibility_y, layout.font(16.0), visibility_color);
+    if palette.shape_cues {
+        // A filled dot for revealed hands and a hollow one for hidden hands
+        let text_width = measure_text(&visibility_text, None, layout.font(16.0) as u16, 1.0).width;
+        let dot_x = layout.header.center().x - text_width / 2.0 - layout.font(12.0);
+        let dot_y = visibility_y - layout.font(5.0);
+        if dice_revealed {
+            draw_circle(dot_x, dot_y, layout.font(5.0), visibility_color);
+        } else {
+            draw_circle_lines(dot_x, dot_y, layout.font(5.0), 2.0, visibility_color);
+        }
+    }
+    if let Some(notice) = &render_state.notice {
+        draw_centered_text(notice, layout.header.center().x, visibility_y + layout.font(22.0), layout.font(18.0), palette.danger);
+    }
+
+    // Draw the table the players sit around
+    draw_ellipse(
+        layout.table.center().x,
+        layout.table.center().y,
+        layout.table.w / 2.0,
+        layout.table.h / 2.0,
+        0.0,
+        palette.table,
     );
 
     // Draw all players and their dice
-    let start_y = 120.0;
-    let dice_size = 60.0;
-    let spacing = 80.0;
+    let dice_size = layout.dice_size;
+    let dice_scale = dice_size / 60.0;
     
     for player in 0..game.player_count as usize {
-        let player_y = start_y + (player as f32 * 130.0);
+        let seat = layout.seat_rect(player);
         
-        // Draw player name
-        draw_text(
+        // Draw player name, marked with the seat's colour from the history panel
+        draw_centered_text(
             &game.player_names[player],
-            screen_width() / 2.0 - 50.0,
-            player_y,
-            24.0,
-            BLACK,
+            seat.center().x,
+            seat.y + layout.font(20.0),
+            layout.font(24.0),
+            palette.text,
         );
+        let name_width = measure_text(&game.player_names[player], None, layout.font(24.0) as u16, 1.0).width;
+        draw_seat_swatch(player, seat.center().x - name_width / 2.0 - layout.font(16.0), seat.y + layout.font(8.0), layout);
         
         // Draw dice count
-        draw_text(
-            &format!("Dice: {}", game.current_player_dice_count[player]),
-            screen_width() / 2.0 - 30.0,
-            player_y + 25.0,
-            16.0,
-            DARKGRAY,
+        draw_centered_text(
+            &tr_count("game.dice_count", game.current_player_dice_count[player].into(), &[]),
+            seat.center().x,
+            seat.y + layout.font(38.0),
+            layout.font(16.0),
+            palette.muted_text,
         );
         
+        // Countdown bar under the name of a player on the clock, turning red as time runs out
+        if let Some((_, remaining)) = render_state.turn_time_left.filter(|(timed_player, _)| *timed_player as usize == player) {
+            let bar = Rect::new(seat.x, seat.y + layout.font(44.0), seat.w, layout.font(6.0));
+            let running_out = remaining <= 0.25;
+            let bar_color = if running_out { palette.danger } else { palette.positive };
+            draw_rectangle(bar.x, bar.y, bar.w, bar.h, palette.panel);
+            draw_rectangle(bar.x, bar.y, bar.w * remaining, bar.h, bar_color);
+            if running_out && palette.shape_cues {
+                draw_rectangle_lines(bar.x, bar.y, bar.w, bar.h, 2.0, bar_color);
+                draw_text("!", bar.right() + layout.font(4.0), bar.bottom() + layout.font(4.0), layout.font(20.0), bar_color);
+            }
+        }
+
         // Draw dice for this player
-        let dice_count = game.current_player_dice_count[player] as usize;
-        for die in 0..dice_count {
-            let dice_x = screen_width() / 2.0 - (dice_count as f32 * spacing) / 2.0 + (die as f32 * spacing);
-            let dice_y = player_y + 60.0;
-            
-            // Draw dice background
-            draw_rectangle(
-                dice_x - dice_size / 2.0,
-                dice_y - dice_size / 2.0,
-                dice_size,
-                dice_size,
-                GRAY,
-            );
+        let (dice_count, player_dice) = match reveal {
+            Some(reveal) => (reveal.hands.dice_count[player] as usize, reveal.hands.player_dice[player]),
+            None => (game.current_player_dice_count[player] as usize, game.player_dice[player]),
+        };
+        let player_style = render_state.dice_style(player);
+        for (die, &face_value) in player_dice.iter().enumerate().take(dice_count) {
+            let dice_center = layout.dice_center(player, die, dice_count);
+            let mut dice_x = dice_center.x;
+            let mut dice_y = dice_center.y;
+            let mut style = player_style.clone();
+            let mut matching = false;
+
+            // Dice shake in place while the next round is rolled
+            if stage == AnimationStage::Rolling {
+                dice_x += gen_range(-4.0, 4.0) * dice_scale;
+                dice_y += gen_range(-4.0, 4.0) * dice_scale;
+            }
+
+            if let Some(reveal) = reveal {
+                // Light up dice matching the challenged face once every hand is showing
+                if stage != AnimationStage::Flipping && reveal.counts_towards_bet(face_value, &game.rules) {
+                    style.body = palette.matching_die;
+                    style.marks = palette.pips;
+                    matching = true;
+                }
+
+                // The loser's die slides up and fades off the table
+                if let AnimationStage::RemovingDie(progress) = stage {
+                    if player == reveal.losing_player as usize && die == dice_count - 1 {
+                        dice_y -= progress * dice_size;
+                        style.body.a *= 1.0 - progress;
+                        style.marks.a *= 1.0 - progress;
+                    }
+                }
+            }
             
-            // Only show dice dots if:
-            // 1. It's the current player (player 0) - they can always see their own dice
-            // 2. Dice have been revealed after a call action
-            if player == 0 || dice_revealed {
-                draw_dice_dots(dice_x, dice_y, game.player_dice[player][die]);
+            // Only show the face if:
+            // 1. It's the viewing player's own hand - they can always see their own dice
+            // 2. Their hand has been flipped over after a call action
+            // Hidden dice show a question mark.
+            let own_hand = viewer == Viewer::Player(player as u8) || viewer == Viewer::Replay;
+            let flipped = dice_revealed && animation.is_some_and(|animation| animation.is_hand_flipped(player));
+            let shown_value = if own_hand && stage == AnimationStage::Rolling {
+                Some(gen_range(1, 7))
+            } else if own_hand || flipped {
+                Some(face_value)
             } else {
-                // Draw question marks for hidden dice
-                draw_text(
-                    "?",
-                    dice_x - 8.0,
-                    dice_y + 8.0,
-                    24.0,
-                    BLACK,
-                );
+                None
+            };
+            draw_die(dice_x, dice_y, dice_size, shown_value, &style);
+            if matching && palette.shape_cues {
+                draw_rectangle_lines(dice_x - dice_size / 2.0, dice_y - dice_size / 2.0, dice_size, dice_size, 4.0 * dice_scale, palette.focus);
             }
         }
     }
 
-    // Draw betting history
-    if !game.bets.is_empty() {
-        let bet_list_offset = screen_height() - 600.0;
-        draw_text(
-            "Betting History:",
-            50.0,
-            bet_list_offset,
-            20.0,
-            BLACK,
-        );
-        
-        for (i, bet) in game.bets.iter().enumerate() {
-            let (player, dice_count, face_value) = bet;
-            draw_text(
-                &format!("Player {}: {} dice showing {}", player + 1, dice_count, face_value),
-                50.0,
-                bet_list_offset + ((1.0 + i as f32) * 20.0),
-                16.0,
-                DARKGRAY,
-            );
+    render_history(game, render_state, layout, palette);
+
+    // Show how the last call was settled while it is revealed. Omniscient spectators
+    // see every hand from the round before for the whole of the next one.
+    if let Some(round) = reveal {
+        render_round_resolution(game, round, layout, palette);
+    } else if game.phase == Phase::Revealing && game.last_round.is_some() {
+        // A called round held on the table, in a replay or a tutorial
+        if let Some(round) = &game.last_round {
+            render_round_resolution(game, round, layout, palette);
+        }
+    } else if let (true, Some(player)) = (render_state.show_hints, viewer.seat()) {
+        render_hints(game, player, layout, palette);
+    } else if viewer == Viewer::OmniscientSpectator {
+        match game.previous_round_hands() {
+            Some(hands) => render_previous_round_hands(game, hands, layout, palette),
+            None => {
+                draw_text(
+                    &tr("game.first_round"),
+                    layout.side_panel.x,
+                    layout.side_panel.y + layout.font(16.0),
+                    layout.font(16.0),
+                    palette.muted_text,
+                );
+            }
         }
     }
 
+    // Only a seated player gets controls
+    let Some(seat) = viewer.seat() else {
+        return;
+    };
+
     // Draw UI controls
-    let ui_y = screen_height() - 100.0;
-    
+    let call_button = layout.call_button();
+    let bet_button = layout.bet_button();
+
     // Call button
-    let call_button_color = if game.bets.is_empty() { DARKGRAY } else { BLUE };
-    draw_rectangle(50.0, ui_y, 120.0, 40.0, call_button_color);
-    draw_text(
-        "Call",
-        100.0,
-        ui_y + 15.0,
-        20.0,
-        WHITE,
-    );
+    let can_call = !game.bets.is_empty();
+    let call_button_color = if can_call { palette.button } else { palette.disabled };
+    draw_control_button(call_button, &tr("game.call"), call_button_color, can_call, layout, palette);
     
-    // Bet button
-    draw_rectangle(200.0, ui_y, 120.0, 40.0, GREEN);
-    draw_text(
-        "Bet",
-        250.0,
-        ui_y + 15.0,
-        20.0,
-        WHITE,
-    );
-    
-    // Dice count dropdown
-    draw_rectangle(350.0, ui_y, 80.0, 40.0, LIGHTGRAY);
-    draw_text(
-        &format!("{}", render_state.selected_dice_count),
-        380.0,
-        ui_y + 15.0,
-        20.0,
-        BLACK,
-    );
-    draw_text(
-        "Dice",
-        350.0,
-        ui_y - 20.0,
-        14.0,
-        BLACK,
+    // Bet button (greyed out when the selected bet is not a legal raise)
+    let bet_is_legal = is_legal_bet(game, render_state.selected_bet());
+    let bet_button_color = if bet_is_legal { palette.confirm } else { palette.disabled };
+    draw_control_button(bet_button, &tr("game.bet"), bet_button_color, bet_is_legal, layout, palette);
+
+    render_bid_picker(game, render_state, layout, seat);
+
+    // Minimum raise shortcut
+    let minimum_raise_rect = layout.minimum_raise_button();
+    draw_rectangle(minimum_raise_rect.x, minimum_raise_rect.y, minimum_raise_rect.w, minimum_raise_rect.h, palette.panel);
+    draw_centered_text(
+        &tr("game.minimum_raise"),
+        minimum_raise_rect.center().x,
+        minimum_raise_rect.center().y + layout.font(6.0),
+        layout.font(20.0),
+        palette.text,
     );
-    
-    // Face value dropdown
-    draw_rectangle(450.0, ui_y, 80.0, 40.0, LIGHTGRAY);
+
+    // Focus ring around the control keyboard input acts on
+    let focus_rect = match render_state.focus {
+        Focus::Call => call_button,
+        Focus::Bet => bet_button,
+        Focus::BidPicker => layout.bid_picker(),
+        Focus::MinimumRaise => minimum_raise_rect,
+    };
+    let ring_gap = 4.0 * layout.scale;
+    draw_rectangle_lines(focus_rect.x - ring_gap, focus_rect.y - ring_gap, focus_rect.w + 2.0 * ring_gap, focus_rect.h + 2.0 * ring_gap, 3.0, palette.focus);
+
+    // Explain why the bet button is disabled when it is hovered
+    let (mouse_x, mouse_y) = mouse_position();
+    if !bet_is_legal && bet_button.contains(Vec2::new(mouse_x, mouse_y)) {
+        let tooltip = match game.bets.last() {
+            Some((_, dice_count, face_value)) => tr_count("game.bet_too_low", (*dice_count).into(), &[("face", face_value)]),
+            None => tr("game.pick_a_bet"),
+        };
+        let font_size = layout.font(16.0);
+        let tooltip_size = measure_text(&tooltip, None, font_size as u16, 1.0);
+        draw_rectangle(mouse_x + 12.0, mouse_y - font_size * 1.75, tooltip_size.width + 12.0, font_size * 1.4, palette.text);
+        draw_text(
+            &tooltip,
+            mouse_x + 18.0,
+            mouse_y - font_size * 0.75,
+            font_size,
+            palette.background,
+        );
+    }
+}
+
+/// A call or bet button. With shape cues on, a disabled button's label is struck through
+/// so it doesn't rely on the button's colour alone.
+fn draw_control_button(rect: Rect, label: &str, color: Color, enabled: bool, layout: &Layout, palette: &Palette) {
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, color);
+    let text_y = rect.center().y + layout.font(6.0);
+    draw_centered_text(label, rect.center().x, text_y, layout.font(20.0), palette.button_text);
+    if !enabled && palette.shape_cues {
+        let half_width = measure_text(label, None, layout.font(20.0) as u16, 1.0).width / 2.0 + layout.font(4.0);
+        let strike_y = text_y - layout.font(6.0);
+        draw_line(rect.center().x - half_width, strike_y, rect.center().x + half_width, strike_y, 2.0, palette.button_text);
+    }
+}
+
+/// Draws text horizontally centred on `x`
+fn draw_centered_text(text: &str, x: f32, y: f32, font_size: f32, color: Color) {
+    let size = measure_text(text, None, font_size as u16, 1.0);
+    draw_text(text, x - size.width / 2.0, y, font_size, color);
+}
+
+/// Lists the legal raises, one row per dice count, with the selected bet outlined
+fn render_bid_picker(game: &Game, render_state: &RenderState, layout: &Layout, seat: u8) {
+    let picker = layout.bid_picker();
+    let palette = &render_state.palette;
+    // The faces are drawn in the player's own dice
+    let style = render_state.dice_style(seat as usize);
+    draw_rectangle(picker.x, picker.y, picker.w, picker.h, palette.panel);
     draw_text(
-        &format!("{}", render_state.selected_face_value),
-        480.0,
-        ui_y + 15.0,
-        20.0,
-        BLACK,
+        &tr_count("game.selected", render_state.selected_dice_count.into(), &[("face", &render_state.selected_face_value)]),
+        picker.x,
+        picker.y - layout.font(8.0),
+        layout.font(14.0),
+        palette.text,
     );
+
+    let rows = picker_rows(game);
+    if rows.is_empty() {
+        draw_text(
+            &tr("game.no_higher_bet"),
+            picker.x + layout.font(10.0),
+            picker.y + layout.font(24.0),
+            layout.font(16.0),
+            palette.text,
+        );
+        return;
+    }
+
+    for (visible_row, (dice_count, faces)) in rows.iter().skip(render_state.picker_scroll).take(PICKER_VISIBLE_ROWS).enumerate() {
+        let row_y = picker.y + visible_row as f32 * layout.picker_row_height();
+        draw_text(
+            &format!("{} x", dice_count),
+            picker.x + layout.font(8.0),
+            row_y + layout.font(24.0),
+            layout.font(20.0),
+            palette.text,
+        );
+
+        for &face_value in faces {
+            let icon = layout.picker_icon(visible_row, face_value);
+            draw_die(icon.center().x, icon.center().y, icon.w, Some(face_value), &style);
+            if (*dice_count, face_value) == render_state.selected_bet() {
+                draw_rectangle_lines(icon.x - 2.0, icon.y - 2.0, icon.w + 4.0, icon.h + 4.0, 3.0, palette.focus);
+            }
+        }
+    }
+
+    // Hint that there are more rows to scroll to
+    if render_state.picker_scroll > 0 {
+        draw_text("^", picker.right() - layout.font(14.0), picker.y + layout.font(14.0), layout.font(20.0), palette.text);
+    }
+    if render_state.picker_scroll + PICKER_VISIBLE_ROWS < rows.len() {
+        draw_text("v", picker.right() - layout.font(14.0), picker.bottom() - layout.font(4.0), layout.font(20.0), palette.text);
+    }
+}
+
+/// The bids of every round so far: earlier rounds as one-line summaries that open to show
+/// their bids, then the round being bid on, newest at the bottom
+fn render_history(game: &Game, render_state: &RenderState, layout: &Layout, palette: &Palette) {
+    let panel = layout.history_panel;
+    let line_height = layout.font(20.0);
+    let rounds = round_history(&game.events);
+    draw_text(&tr("game.history"), panel.x, panel.y + line_height, layout.font(20.0), palette.text);
+
+    let lines = history_lines(&rounds, &render_state.expanded_rounds);
+    let (visible, _) = visible_lines(lines.len(), history_rows(layout), render_state.history_scroll);
+    let indent = layout.font(14.0);
+    for (row, line) in lines[visible.clone()].iter().enumerate() {
+        let y = panel.y + (2.0 + row as f32) * line_height;
+        match *line {
+            HistoryLine::Round { index, expanded } => {
+                let round = &rounds[index];
+                let heading = match &round.outcome {
+                    Some(outcome) => {
+                        let loser = outcome.losing_player();
+                        let key = if round.eliminated.contains(&loser) { "history.round_out" } else { "history.round_lost" };
+                        let summary = tr_args(key, &[("round", &round.number), ("player", &game.player_names[loser as usize])]);
+                        format!("{} {}", if expanded { "-" } else { "+" }, summary)
+                    }
+                    None => tr_args("history.round", &[("round", &round.number)]),
+                };
+                draw_text(&heading, panel.x, y, layout.font(16.0), palette.text);
+            }
+            HistoryLine::Bid { round, bid } => {
+                let (player, dice_count, face_value) = rounds[round].bids[bid];
+                draw_seat_swatch(player as usize, panel.x + indent, y - layout.font(10.0), layout);
+                draw_text(
+                    &tr_count("game.history_bet", dice_count.into(), &[("player", &game.player_names[player as usize]), ("face", &face_value)]),
+                    panel.x + indent + layout.font(14.0),
+                    y,
+                    layout.font(16.0),
+                    palette.muted_text,
+                );
+            }
+            HistoryLine::Outcome(index) => {
+                let outcome = match &rounds[index].outcome {
+                    Some(RoundOutcome::Called(resolution)) => tr_args(
+                        "history.called",
+                        &[("player", &game.player_names[resolution.caller as usize]), ("count", &resolution.actual_count)],
+                    ),
+                    Some(RoundOutcome::TimedOut(player)) => tr_args("history.timed_out", &[("player", &game.player_names[*player as usize])]),
+                    None => continue,
+                };
+                draw_text(&outcome, panel.x + indent, y, layout.font(16.0), palette.muted_text);
+            }
+            HistoryLine::NoBids => {
+                draw_text(&tr("history.no_bids"), panel.x + indent, y, layout.font(16.0), palette.muted_text);
+            }
+        }
+    }
+
+    // Hint that there are more lines to scroll to
+    if visible.start > 0 {
+        draw_text("^", panel.right() - layout.font(14.0), panel.y + line_height, layout.font(20.0), palette.text);
+    }
+    if visible.end < lines.len() {
+        draw_text("v", panel.right() - layout.font(14.0), panel.y + (1.0 + visible.len() as f32) * line_height, layout.font(20.0), palette.text);
+    }
+}
+
+/// A small square in a seat's colour, with its top left corner at (x, y)
+fn draw_seat_swatch(player: usize, x: f32, y: f32, layout: &Layout) {
+    let size = layout.font(10.0);
+    draw_rectangle(x, y, size, size, SEAT_COLORS[player % SEAT_COLORS.len()]);
+}
+
+/// Odds for the viewing player's seat, worked out from their own hand only
+fn render_hints(game: &Game, player: u8, layout: &Layout, palette: &Palette) {
+    let panel = layout.side_panel;
+    let line_height = layout.font(20.0);
+    let hint = hint(game, player);
+
+    draw_text(&tr("hints.title"), panel.x, panel.y + line_height, layout.font(20.0), palette.text);
+
+    let mut lines = vec![match hint.bid_probability {
+        Some(probability) => tr_args("hints.bid_holds", &[("percent", &format!("{:.0}", probability * 100.0))]),
+        None => tr("hints.no_bid"),
+    }];
+    lines.push(tr("hints.expected_counts"));
+    // Indented under their heading; catalogs trim leading spaces
+    lines.extend((1..=6).map(|face| format!("  {}", tr_args("hints.expected_face", &[("face", &face), ("count", &format!("{:.1}", hint.expected_counts[face - 1]))]))));
+    if let Some(suggestion) = hint.suggestion {
+        lines.push(match suggestion.bet {
+            Some((dice_count, face_value)) => tr_args("hints.suggest_bet", &[("count", &dice_count), ("face", &face_value)]),
+            None => tr("hints.suggest_call"),
+        });
+    }
+
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(line, panel.x, panel.y + (2.0 + i as f32) * line_height, layout.font(16.0), palette.muted_text);
+    }
+}
+
+/// Every hand from the round before, for omniscient spectators. Hands from a round that
+/// ended in a timeout were never revealed at the table.
+fn render_previous_round_hands(game: &Game, hands: &RoundHands, layout: &Layout, palette: &Palette) {
+    let panel = layout.side_panel;
+    let line_height = layout.font(20.0);
+    draw_text(&tr("game.previous_hands"), panel.x, panel.y + line_height, layout.font(20.0), palette.text);
+
+    for player in 0..game.player_count as usize {
+        let dice_count = hands.dice_count[player] as usize;
+        let faces: Vec<String> = hands.player_dice[player][..dice_count].iter().map(|face| face.to_string()).collect();
+        draw_text(
+            &format!("{}: {}", game.player_names[player], faces.join(" ")),
+            panel.x,
+            panel.y + ((2.0 + player as f32) * line_height),
+            layout.font(16.0),
+            palette.muted_text,
+        );
+    }
+}
+
+/// Summary of how the last call was settled, with every hand and its matching dice picked out
+fn render_round_resolution(game: &Game, round: &RoundResolution, layout: &Layout, palette: &Palette) {
+    let panel = layout.side_panel;
+    let line_height = layout.font(20.0);
+    let (betting_player, bet_dice_count, bet_face_value) = round.bet;
+
     draw_text(
-        "Face",
-        450.0,
-        ui_y - 20.0,
-        14.0,
-        BLACK,
+        &tr("round.title"),
+        panel.x,
+        panel.y + line_height,
+        layout.font(20.0),
+        palette.text,
     );
+
+    let summary = [
+        tr_count("round.bet", bet_dice_count.into(), &[("player", &game.player_names[betting_player as usize]), ("face", &bet_face_value)]),
+        tr_args("round.called", &[("player", &game.player_names[round.caller as usize])]),
+        if round.wild_count > 0 {
+            tr_args("round.actual_count_wild", &[("count", &round.actual_count), ("wild", &round.wild_count)])
+        } else {
+            tr_args("round.actual_count", &[("count", &round.actual_count)])
+        },
+        tr_args("round.lost_die", &[("player", &game.player_names[round.losing_player as usize])]),
+    ];
+    for (i, line) in summary.iter().enumerate() {
+        draw_text(
+            line,
+            panel.x,
+            panel.y + ((2.0 + i as f32) * line_height),
+            layout.font(16.0),
+            palette.muted_text,
+        );
+    }
+
+    // Every hand from the round, with dice matching the called face highlighted
+    let hands_y = panel.y + ((3.0 + summary.len() as f32) * line_height);
+    for player in 0..game.player_count as usize {
+        let line_y = hands_y + (player as f32 * line_height);
+        draw_text(
+            &format!("{}:", game.player_names[player]),
+            panel.x,
+            line_y,
+            layout.font(16.0),
+            palette.muted_text,
+        );
+
+        let dice_count = round.hands.dice_count[player] as usize;
+        for (die, &face_value) in round.hands.player_dice[player].iter().enumerate().take(dice_count) {
+            let face_x = panel.x + layout.font(80.0) + (die as f32 * layout.font(18.0));
+            let matching = round.counts_towards_bet(face_value, &game.rules);
+            if matching {
+                draw_rectangle(face_x - layout.font(3.0), line_y - layout.font(13.0), layout.font(15.0), layout.font(17.0), palette.matching_die);
+            }
+            draw_text(
+                &face_value.to_string(),
+                face_x,
+                line_y,
+                layout.font(16.0),
+                if matching { palette.pips } else { palette.text },
+            );
+            if matching && palette.shape_cues {
+                draw_line(face_x - layout.font(2.0), line_y + layout.font(3.0), face_x + layout.font(11.0), line_y + layout.font(3.0), 2.0, palette.text);
+            }
+        }
+    }
+}
+
+pub fn handle_handoff_click() -> bool {
+    if is_mouse_button_pressed(MouseButton::Left) {
+        let (mouse_x, mouse_y) = mouse_position();
+        let mouse_pos = Vec2::new(mouse_x, mouse_y);
+
+        let ready_button_rect = Rect::new(screen_width() / 2.0 - 80.0, screen_height() / 2.0 + 20.0, 160.0, 50.0);
+        if ready_button_rect.contains(mouse_pos) {
+            return true;
+        }
+    }
+    false
 }
 
 pub fn handle_restart_click() -> bool {
@@ -281,4 +981,16 @@ pub fn handle_restart_click() -> bool {
         }
     }
     false
-} 
\ No newline at end of file
+}
+
+pub fn handle_main_menu_click() -> bool {
+    is_mouse_button_pressed(MouseButton::Left) && winner_button_rect(1.0).contains(Vec2::from(mouse_position()))
+}
+
+pub fn handle_stats_click() -> bool {
+    is_mouse_button_pressed(MouseButton::Left) && winner_button_rect(2.0).contains(Vec2::from(mouse_position()))
+}
+
+pub fn handle_save_game_click() -> bool {
+    is_mouse_button_pressed(MouseButton::Left) && winner_button_rect(3.0).contains(Vec2::from(mouse_position()))
+}
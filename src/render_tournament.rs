@@ -0,0 +1,79 @@
+// Standings screen shown between the games of a match or tournament
+
+use macroquad::prelude::*;
+
+use crate::i18n::{tr, tr_args};
+use crate::menu::{draw_title, menu_button};
+use crate::theme::Palette;
+use crate::tournament::Tournament;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StandingsAction {
+    NextGame,
+    SaveResults,
+    MainMenu,
+}
+
+/// The standings table, the next table to play and buttons to carry on, save the results or leave
+pub fn render_standings(tournament: &Tournament, palette: &Palette, message: Option<&str>) -> Option<StandingsAction> {
+    clear_background(palette.background);
+    let scale = (screen_width() / 1200.0).min(screen_height() / 800.0);
+
+    let title = match tournament.champion() {
+        Some(champion) => tr_args("standings.champion", &[("format", &tournament.format.label()), ("name", &tournament.entrants[champion].name)]),
+        None => tr_args("standings.after_round", &[("format", &tournament.format.label()), ("round", &tournament.round)]),
+    };
+    draw_title(&title, screen_width() / 2.0, 60.0 * scale, scale, palette);
+
+    let left = screen_width() / 2.0 - 330.0 * scale;
+    let row = |index: f32| 130.0 * scale + index * 32.0 * scale;
+    let columns = [
+        (String::new(), 0.0),
+        (tr("common.name"), 40.0),
+        (tr("standings.points"), 330.0),
+        (tr("standings.wins"), 440.0),
+        (tr("common.games"), 540.0),
+    ];
+    for (heading, x) in &columns {
+        draw_text(heading, left + x * scale, row(0.0), 22.0 * scale, palette.muted_text);
+    }
+    for (place, (entrant, standing)) in tournament.standings().iter().enumerate() {
+        let values = [
+            format!("{}.", place + 1),
+            tournament.entrants[*entrant].name.clone(),
+            standing.points.to_string(),
+            standing.wins.to_string(),
+            standing.games.to_string(),
+        ];
+        for ((_, x), value) in columns.iter().zip(&values) {
+            draw_text(value, left + x * scale, row(place as f32 + 1.0), 22.0 * scale, palette.text);
+        }
+    }
+
+    // Who sits down next
+    if let Some(table) = tournament.current_table() {
+        let names: Vec<&str> = table.iter().map(|&entrant| tournament.entrants[entrant].name.as_str()).collect();
+        draw_text(
+            &tr_args("standings.next_table", &[("names", &names.join(", "))]),
+            left,
+            screen_height() - 110.0 * scale,
+            22.0 * scale,
+            palette.text,
+        );
+    }
+    if let Some(message) = message {
+        draw_text(message, left, screen_height() - 20.0 * scale, 18.0 * scale, palette.muted_text);
+    }
+
+    let button = |column: f32| Rect::new(left + column * 230.0 * scale, screen_height() - 90.0 * scale, 200.0 * scale, 50.0 * scale);
+    if !tournament.is_finished() && menu_button(button(0.0), &tr("standings.next_game"), scale, palette) {
+        return Some(StandingsAction::NextGame);
+    }
+    if menu_button(button(1.0), &tr("standings.save_results"), scale, palette) {
+        return Some(StandingsAction::SaveResults);
+    }
+    if menu_button(button(2.0), &tr("common.main_menu"), scale, palette) {
+        return Some(StandingsAction::MainMenu);
+    }
+    None
+}
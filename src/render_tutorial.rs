@@ -0,0 +1,127 @@
+// Tutorial callouts drawn over the game table
+
+use macroquad::prelude::*;
+
+use crate::game::Game;
+use crate::i18n::{tr, tr_args};
+use crate::layout::Layout;
+use crate::menu::menu_button;
+use crate::theme::Palette;
+use crate::tutorial::{describe_move, Step, Target, Tutorial};
+
+const CALLOUT_WIDTH: f32 = 380.0;
+const CALLOUT_COLOR: Color = Color::new(1.0, 0.98, 0.85, 0.97);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TutorialAction {
+    DismissNote,
+    NextLesson,
+    MainMenu,
+}
+
+fn target_rect(target: Target, layout: &Layout) -> Rect {
+    match target {
+        Target::Table => layout.table,
+        Target::Hand => layout.seat_rect(0),
+        Target::History => layout.history_panel,
+        Target::Controls => layout.controls,
+        Target::Picker => layout.bid_picker(),
+        Target::Call => layout.call_button(),
+        Target::Panel => layout.side_panel,
+    }
+}
+
+/// Splits text into lines that fit the callout
+fn wrap_text(text: &str, width: f32, font_size: f32) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+        if !line.is_empty() && measure_text(&candidate, None, font_size as u16, 1.0).width > width {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = candidate;
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// A box of text beside `target`, above it when there is room and below otherwise, with
+/// buttons along its bottom. Returns the index of the button clicked.
+fn draw_callout(target: Option<Rect>, text: &str, buttons: &[&str], layout: &Layout, palette: &Palette) -> Option<usize> {
+    let width = CALLOUT_WIDTH * layout.scale;
+    let font_size = layout.font(18.0);
+    let line_height = layout.font(22.0);
+    let lines = wrap_text(text, width - layout.font(24.0), font_size);
+    let button_height = layout.font(36.0);
+    let height = lines.len() as f32 * line_height + button_height + layout.font(36.0);
+
+    let (x, y) = match target {
+        Some(target) => {
+            draw_rectangle_lines(target.x - 4.0, target.y - 4.0, target.w + 8.0, target.h + 8.0, 4.0, ORANGE);
+            let x = (target.center().x - width / 2.0).clamp(0.0, (screen_width() - width).max(0.0));
+            let y = if target.y - height - 12.0 >= 0.0 { target.y - height - 12.0 } else { (target.bottom() + 12.0).min(screen_height() - height) };
+            (x, y)
+        }
+        None => ((screen_width() - width) / 2.0, (screen_height() - height) / 2.0),
+    };
+
+    draw_rectangle(x, y, width, height, CALLOUT_COLOR);
+    draw_rectangle_lines(x, y, width, height, 2.0, ORANGE);
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(line, x + layout.font(12.0), y + layout.font(24.0) + i as f32 * line_height, font_size, BLACK);
+    }
+
+    let button_width = layout.font(130.0);
+    let mut clicked = None;
+    for (i, label) in buttons.iter().enumerate() {
+        let button_x = x + width - (i + 1) as f32 * (button_width + layout.font(10.0));
+        let rect = Rect::new(button_x, y + height - button_height - layout.font(10.0), button_width, button_height);
+        if menu_button(rect, label, layout.scale, palette) {
+            clicked = Some(i);
+        }
+    }
+    clicked
+}
+
+/// The lesson banner and the current callout, drawn after the table
+pub fn render_tutorial(tutorial: &Tutorial, game: &Game, palette: &Palette) -> Option<TutorialAction> {
+    let layout = Layout::new(screen_width(), screen_height(), game.player_count);
+
+    // Which lesson this is, and what the learner has to do next
+    let banner = tr_args(
+        "tutorial.banner",
+        &[
+            ("lesson", &(tutorial.lesson + 1)),
+            ("count", &tutorial.lessons.len()),
+            ("title", &tutorial.current_lesson().title),
+        ],
+    );
+    draw_text(&banner, layout.font(12.0), layout.font(24.0), layout.font(22.0), palette.text);
+    if let (Some(expected), false) = (tutorial.expected_move(), game.winner.is_some()) {
+        draw_text(&tr_args("tutorial.your_move", &[("move", &describe_move(expected))]), layout.font(12.0), layout.font(48.0), layout.font(20.0), palette.positive);
+    }
+    if let Some(message) = &tutorial.message {
+        draw_text(message, layout.font(12.0), layout.font(70.0), layout.font(18.0), palette.danger);
+    }
+
+    match tutorial.current_step() {
+        Some(Step::Note { target, text }) => {
+            // Callouts about the table can't point at it once the game is over
+            let target = game.winner.is_none().then(|| target_rect(*target, &layout));
+            draw_callout(target, text, &[&tr("tutorial.next")], &layout, palette).map(|_| TutorialAction::DismissNote)
+        }
+        Some(_) => None,
+        None if tutorial.is_last_lesson() => {
+            draw_callout(None, &tr("tutorial.finished"), &[&tr("common.main_menu")], &layout, palette).map(|_| TutorialAction::MainMenu)
+        }
+        None => {
+            let text = tr_args("tutorial.lesson_complete", &[("title", &tutorial.current_lesson().title)]);
+            draw_callout(None, &text, &[&tr("tutorial.next_lesson"), &tr("common.main_menu")], &layout, palette).map(|button| match button {
+                0 => TutorialAction::NextLesson,
+                _ => TutorialAction::MainMenu,
+            })
+        }
+    }
+}
@@ -0,0 +1,47 @@
+// Stepping through a game read from a transcript
+
+use std::path::Path;
+
+use crate::game::Game;
+use crate::notation;
+
+pub struct Replay {
+    pub states: Vec<Game>, // The table at each deal and after each move
+    pub position: usize,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let states = notation::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(Self { states, position: 0 })
+    }
+
+    pub fn current(&self) -> &Game {
+        &self.states[self.position]
+    }
+
+    /// Moves forwards or backwards through the game, stopping at either end
+    pub fn step(&mut self, delta: isize) {
+        self.position = self.position.saturating_add_signed(delta).min(self.states.len() - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_stops_at_either_end() {
+        let mut replay = Replay {
+            states: vec![Game::new(), Game::new(), Game::new()],
+            position: 0,
+        };
+        replay.step(-1);
+        assert_eq!(replay.position, 0);
+        replay.step(2);
+        assert_eq!(replay.position, 2);
+        replay.step(1);
+        assert_eq!(replay.position, 2);
+    }
+}
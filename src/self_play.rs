@@ -0,0 +1,76 @@
+// Headless all-AI games, used to rank AI strategies on the ratings leaderboard
+
+use macroquad::rand::{gen_range, srand};
+
+use crate::ai::ai_decide_action;
+use crate::event_log::{log_transition, start_log, Source};
+use crate::game::{advance_phase, take_action, Game, Phase};
+use crate::ratings::Ratings;
+use crate::settings::{SeatKind, Settings};
+
+// A game that hasn't finished after this many moves has stalled
+const MAX_MOVES: usize = 10_000;
+
+/// Plays `game_count` games without a window, giving each seat a random AI strategy,
+/// and rates every result
+pub fn run_self_play(game_count: u32, settings: &Settings, ratings: &mut Ratings) -> Result<(), String> {
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    srand(seed);
+
+    let ai_seats = &SeatKind::ALL[1..];
+    for _ in 0..game_count {
+        let mut game_settings = settings.clone();
+        for seat in game_settings.seats.iter_mut() {
+            *seat = ai_seats[gen_range(0, ai_seats.len())];
+        }
+
+        let game = play_game(&game_settings)?;
+        ratings.record_game(&game, &game_settings.seats);
+    }
+    Ok(())
+}
+
+/// Plays one game to the end with every seat taken by the AI, logging its events if the
+/// settings ask for it
+fn play_game(settings: &Settings) -> Result<Game, String> {
+    let mut game = settings.new_game();
+    let mut event_log = start_log(Source::SelfPlay, &game, settings);
+
+    for _ in 0..MAX_MOVES {
+        if game.winner.is_some() {
+            return Ok(game);
+        }
+        let next = match game.phase {
+            Phase::Bidding => {
+                let action = ai_decide_action(&game, settings.ai_difficulty(game.current_player));
+                take_action(&game, &action)?
+            }
+            Phase::Revealing | Phase::Dealing => advance_phase(&game)?,
+        };
+        log_transition(&mut event_log, &game, &next);
+        game = next;
+    }
+    Err(format!("Self-play game did not finish within {} moves", MAX_MOVES))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_play_games_finish_and_are_rated() {
+        let mut settings = Settings::new();
+        settings.player_count = 5;
+        let mut ratings = Ratings::default();
+
+        run_self_play(10, &settings, &mut ratings).unwrap();
+
+        // Every game counts once for each strategy that played in it, however many seats it took
+        assert!(ratings.ratings.iter().all(|(_, rating)| rating.games <= 10));
+        let games: u32 = ratings.ratings.iter().map(|(_, rating)| rating.games).sum();
+        assert!((10..=10 * 3).contains(&games));
+        assert!(ratings.ratings.iter().all(|(name, _)| name.starts_with("AI ")));
+    }
+}
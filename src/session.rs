@@ -0,0 +1,406 @@
+// One game at the table, with the view and animation state that goes with it
+
+use macroquad::rand::gen_range;
+
+use crate::animation::Animation;
+use crate::audio::{sound_cues, SoundEffect};
+use crate::clock::{Clock, TurnTimer};
+use crate::event_log::{log_takeback, log_transition, start_log, EventLog, Source};
+use crate::game::{advance_phase, apply_timeout, take_action, Game, Phase, PlayerAction};
+use crate::render_game::{RenderState, Viewer};
+use crate::settings::Settings;
+use crate::tournament::MatchFormat;
+
+// Seconds a notice stays over the table
+const NOTICE_DURATION: f32 = 3.0;
+
+pub struct Session {
+    pub game: Game,
+    pub settings: Settings, // The setup this game was started from
+    pub viewer: Viewer,
+    pub starting_viewer: Viewer,
+    pub hot_seat: bool, // More than one human at the machine, so hands are hidden between turns
+    pub handoff_to: Option<u8>,
+    pub render_state: RenderState,
+    pub animation: Option<Animation>,
+    pub clock: Clock,
+    pub last_action_time: f32, // Clock reading when the last move was played
+    pub ai_delay: f32, // Seconds the next AI seat thinks before moving
+    pub fast_forward: bool, // Playing AI moves without pausing until a human is up or the round ends
+    pub turn_timer: Option<TurnTimer>, // Running while a human has a turn limit
+    pub recorded: bool, // Whether the finished game has been added to stats and ratings
+    pub history: Vec<Game>, // The table before each of the human's moves, for takebacks
+    pub sound_queue: Vec<(f32, SoundEffect)>, // Sounds waiting to play, with the clock reading to play them at
+    pub event_log: Option<EventLog>, // Open while the settings ask for games to be logged
+    notice_shown_at: f32, // Clock reading when the notice over the table went up
+}
+
+/// Who watches a game set up from the menu: the first human seat, or a spectator if there is none
+fn default_viewer(game: &Game) -> Viewer {
+    (0..game.player_count)
+        .find(|&player| game.is_human[player as usize])
+        .map_or(Viewer::Spectator, Viewer::Player)
+}
+
+impl Session {
+    /// Deals a new game for the setup, watched by `viewer` or the first human seat
+    pub fn new(settings: Settings, viewer: Option<Viewer>) -> Self {
+        Self::with_game(settings.new_game(), settings, viewer)
+    }
+
+    /// Plays a game that has already been dealt, such as a tutorial lesson
+    pub fn with_game(game: Game, settings: Settings, viewer: Option<Viewer>) -> Self {
+        let starting_viewer = viewer.unwrap_or_else(|| default_viewer(&game));
+        let mut render_state = RenderState::new();
+        render_state.palette = settings.palette();
+        render_state.dice_skins = settings.dice_skins[..game.player_count as usize].to_vec();
+        render_state.hints_allowed = settings.hints_allowed();
+        render_state.show_hints = settings.hints && settings.hints_allowed();
+
+        Self {
+            hot_seat: game.human_count() > 1,
+            // Dice tumble on the opening roll
+            animation: Some(Animation::roll(settings.animation_config())),
+            game,
+            settings,
+            viewer: starting_viewer,
+            starting_viewer,
+            handoff_to: None,
+            render_state,
+            clock: Clock::default(),
+            last_action_time: 0.0,
+            ai_delay: 0.0,
+            fast_forward: false,
+            turn_timer: None,
+            recorded: false,
+            history: Vec::new(),
+            // Dice rattle for the opening roll
+            sound_queue: vec![(0.0, SoundEffect::DiceShake)],
+            event_log: None,
+            notice_shown_at: 0.0,
+        }
+    }
+
+    /// Starts over with the same setup and viewer
+    pub fn restart(&mut self) {
+        *self = Self::new(self.settings.clone(), Some(self.starting_viewer));
+        self.start_event_log();
+    }
+
+    /// Opens the event log, if the settings ask for one. This waits until the game is
+    /// actually being played, so the table set up behind the menu never leaves a log.
+    /// Tutorial lessons aren't logged.
+    pub fn start_event_log(&mut self) {
+        self.event_log = start_log(Source::Gui, &self.game, &self.settings);
+    }
+
+    /// Takebacks are only for casual games: one human against the AI, unrated, outside
+    /// a match
+    pub fn undo_allowed(&self) -> bool {
+        !self.settings.rated && self.settings.match_format == MatchFormat::Single && self.game.human_count() == 1
+    }
+
+    /// Rewinds to just before the human's last move, along with any AI moves that followed.
+    /// The dealer is rewound too, so the same hands come up again.
+    pub fn undo(&mut self) -> bool {
+        if !self.undo_allowed() || self.game.winner.is_some() {
+            return false;
+        }
+        let Some(previous) = self.history.pop() else {
+            return false;
+        };
+
+        let takebacks = self.game.takebacks + 1;
+        self.game = previous;
+        self.game.takebacks = takebacks;
+        log_takeback(&mut self.event_log, &self.game);
+        self.animation = None;
+        self.render_state.select_minimum_raise(&self.game);
+        self.last_action_time = self.clock.now();
+        self.fast_forward = false;
+        self.turn_timer = None;
+        self.sound_queue.clear();
+        true
+    }
+
+    /// Plays an action for the current player, resetting the bet selection and starting the
+    /// reveal after a call
+    pub fn apply_action(&mut self, action: &PlayerAction) -> Result<(), String> {
+        let new_game = take_action(&self.game, action)?;
+        if self.undo_allowed() && self.game.is_human[self.game.current_player as usize] {
+            self.history.push(self.game.clone());
+        }
+        self.show(new_game);
+        Ok(())
+    }
+
+    /// Moves on to the table after a move, resetting the bet selection and starting the
+    /// reveal after a call
+    fn show(&mut self, new_game: Game) {
+        if new_game.phase == Phase::Bidding {
+            self.render_state.select_minimum_raise(&new_game);
+        } else {
+            self.render_state.selected_dice_count = 1;
+            self.render_state.selected_face_value = 1;
+        }
+        // Reveal dice after a call
+        if new_game.phase == Phase::Revealing {
+            let animation_config = self.settings.animation_config();
+            self.animation = new_game.last_round.clone().map(|round| Animation::reveal(animation_config, round));
+        }
+        self.queue_sounds(&new_game);
+        log_transition(&mut self.event_log, &self.game, &new_game);
+        // The next AI seat thinks for a little more or less than the pace set for the game
+        self.last_action_time = self.clock.now();
+        self.ai_delay = self.settings.ai_pacing.thinking_delay(gen_range(-1.0, 1.0));
+        self.fast_forward &= new_game.phase == Phase::Bidding && !new_game.is_human[new_game.current_player as usize];
+        self.game = new_game;
+        self.turn_timer = None;
+    }
+
+    /// Moves the table on from the reveal to the deal, and from the deal to the bidding
+    pub fn advance_phase(&mut self) -> Result<(), String> {
+        let new_game = advance_phase(&self.game)?;
+        self.queue_sounds(&new_game);
+        log_transition(&mut self.event_log, &self.game, &new_game);
+        self.game = new_game;
+        Ok(())
+    }
+
+    fn queue_sounds(&mut self, new_game: &Game) {
+        let now = self.clock.now();
+        self.sound_queue.extend(sound_cues(&self.game, new_game).into_iter().map(|(delay, effect)| (now + delay, effect)));
+    }
+
+    /// Shows a short message over the table for a few seconds
+    pub fn show_notice(&mut self, text: String) {
+        self.render_state.notice = Some(text);
+        self.notice_shown_at = self.clock.now();
+    }
+
+    /// Takes the notice down once it has been up long enough
+    pub fn expire_notice(&mut self) {
+        if self.clock.since(self.notice_shown_at) >= NOTICE_DURATION {
+            self.render_state.notice = None;
+        }
+    }
+
+    /// Takes the sounds that are due to play by now off the queue
+    pub fn due_sounds(&mut self) -> Vec<SoundEffect> {
+        let now = self.clock.now();
+        let (due, waiting) = self.sound_queue.iter().partition(|(at, _)| *at <= now);
+        self.sound_queue = waiting;
+        due.into_iter().map(|(_, effect)| effect).collect()
+    }
+
+    /// Whether the AI seat whose turn it is has finished thinking
+    pub fn ai_ready(&self) -> bool {
+        self.fast_forward || self.clock.since(self.last_action_time) >= self.ai_delay
+    }
+
+    /// Skips the current animation and plays the AI moves straight away until the round
+    /// is called or a human is up. Does nothing on a human's turn.
+    pub fn start_fast_forward(&mut self) {
+        let game = &self.game;
+        if game.winner.is_some() || (game.phase == Phase::Bidding && game.is_human[game.current_player as usize]) {
+            return;
+        }
+        if let Some(animation) = &mut self.animation {
+            animation.skip();
+        }
+        self.fast_forward = true;
+    }
+
+    /// Runs the turn limit for a human whose turn it is, once the table is ready for them,
+    /// and plays the configured default if it runs out. Returns whether it ran out.
+    pub fn update_turn_timer(&mut self, table_ready: bool) -> Result<bool, String> {
+        let player = self.game.current_player;
+        let limit = self.settings.turn_time_limit as f32;
+        let timed = limit > 0.0 && table_ready && self.game.winner.is_none() && self.game.phase == Phase::Bidding && self.game.is_human[player as usize];
+        if !timed {
+            self.turn_timer = None;
+            self.render_state.turn_time_left = None;
+            return Ok(false);
+        }
+
+        let clock = self.clock;
+        let timer = *self.turn_timer.get_or_insert_with(|| TurnTimer::start(&clock, player, limit));
+        self.render_state.turn_time_left = Some((player, timer.remaining(&clock)));
+        if !timer.is_expired(&clock) {
+            return Ok(false);
+        }
+
+        let new_game = apply_timeout(&self.game, self.settings.timeout_action)?;
+        self.show(new_game);
+        self.render_state.turn_time_left = None;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ai::{ai_decide_action, AiDifficulty};
+    use crate::game::{advance_phase, Action, TimeoutAction};
+
+    fn casual_session() -> Session {
+        let mut settings = Settings::new();
+        settings.player_count = 3;
+        settings.rated = false;
+        Session::new(settings, None)
+    }
+
+    fn bid(bet: (u8, u8)) -> PlayerAction {
+        PlayerAction {
+            action: Action::Bet,
+            bet: Some(bet),
+        }
+    }
+
+    #[test]
+    fn test_undo_rewinds_past_the_ai_moves() {
+        let mut session = casual_session();
+        let before = session.game.clone();
+        session.apply_action(&bid((1, 2))).unwrap();
+        while session.game.current_player != 0 && session.game.phase == Phase::Bidding {
+            let action = ai_decide_action(&session.game, AiDifficulty::Normal);
+            session.apply_action(&action).unwrap();
+        }
+
+        assert!(session.undo());
+        assert_eq!(session.game.takebacks, 1);
+        assert_eq!(session.game.events, before.events);
+        assert_eq!(session.game.player_dice, before.player_dice);
+        assert_eq!(session.game.current_player, 0);
+        assert!(!session.undo());
+    }
+
+    #[test]
+    fn test_undone_call_deals_the_same_next_round() {
+        let call = PlayerAction {
+            action: Action::Call,
+            bet: None,
+        };
+        let mut session = casual_session();
+        for bet in [(1, 2), (1, 3), (1, 4)] {
+            session.apply_action(&bid(bet)).unwrap();
+        }
+        session.apply_action(&call).unwrap();
+        let dealt = advance_phase(&advance_phase(&session.game).unwrap()).unwrap();
+
+        assert!(session.undo());
+        assert_eq!(session.game.bets.len(), 3);
+        session.apply_action(&call).unwrap();
+        let dealt_again = advance_phase(&advance_phase(&session.game).unwrap()).unwrap();
+        assert_eq!(dealt_again.player_dice, dealt.player_dice);
+    }
+
+    #[test]
+    fn test_no_takebacks_in_rated_or_hot_seat_games() {
+        let mut session = Session::new(Settings::new(), None);
+        session.apply_action(&bid((1, 2))).unwrap();
+        assert!(!session.undo());
+
+        let mut settings = Settings::new();
+        settings.rated = false;
+        settings.set_human_count(2);
+        let mut session = Session::new(settings, None);
+        session.apply_action(&bid((1, 2))).unwrap();
+        assert!(!session.undo());
+    }
+
+    #[test]
+    fn test_turn_timer_plays_the_default_when_it_runs_out() {
+        let mut settings = Settings::new();
+        settings.turn_time_limit = 10;
+        settings.timeout_action = TimeoutAction::LoseDie;
+        let mut session = Session::new(settings, None);
+
+        // The clock doesn't start until the table is ready for the human
+        session.clock.tick(30.0);
+        assert_eq!(session.update_turn_timer(false), Ok(false));
+        assert_eq!(session.update_turn_timer(true), Ok(false));
+        session.clock.tick(9.0);
+        assert_eq!(session.update_turn_timer(true), Ok(false));
+        assert!(session.render_state.turn_time_left.is_some_and(|(player, left)| player == 0 && left < 0.2));
+
+        session.clock.tick(1.0);
+        assert_eq!(session.update_turn_timer(true), Ok(true));
+        assert_eq!(session.game.current_player_dice_count[0], 4);
+        assert_eq!(session.render_state.turn_time_left, None);
+    }
+
+    #[test]
+    fn test_acting_stops_the_turn_timer() {
+        let mut settings = Settings::new();
+        settings.turn_time_limit = 10;
+        let mut session = Session::new(settings, None);
+        session.update_turn_timer(true).unwrap();
+        session.clock.tick(9.0);
+        session.apply_action(&bid((1, 2))).unwrap();
+        session.clock.tick(5.0);
+        // An AI seat is up, so nothing is timed
+        assert_eq!(session.update_turn_timer(true), Ok(false));
+        assert_eq!(session.game.bets.len(), 1);
+    }
+
+    #[test]
+    fn test_fast_forward_lasts_until_a_human_is_up_or_the_round_ends() {
+        let mut session = Session::new(Settings::new(), None);
+        session.start_fast_forward();
+        assert!(!session.fast_forward, "nothing to skip on the human's turn");
+
+        session.apply_action(&bid((1, 2))).unwrap();
+        assert!(!session.ai_ready());
+        session.start_fast_forward();
+        while session.game.phase == Phase::Bidding && session.game.current_player != 0 {
+            assert!(session.ai_ready());
+            let action = ai_decide_action(&session.game, AiDifficulty::Normal);
+            session.apply_action(&action).unwrap();
+        }
+        assert!(!session.fast_forward);
+        assert!(!session.ai_ready());
+    }
+
+    #[test]
+    fn test_sounds_wait_for_their_cue() {
+        let mut session = Session::new(Settings::new(), None);
+        assert_eq!(session.due_sounds(), [SoundEffect::DiceShake]);
+        assert_eq!(session.due_sounds(), []);
+
+        for bet in [(1, 2), (1, 3)] {
+            session.apply_action(&bid(bet)).unwrap();
+        }
+        session.apply_action(&PlayerAction { action: Action::Call, bet: None }).unwrap();
+        assert_eq!(session.due_sounds(), [SoundEffect::Bid, SoundEffect::Bid, SoundEffect::Call]);
+        session.clock.tick(0.5);
+        assert_eq!(session.due_sounds(), [SoundEffect::Reveal]);
+        session.clock.tick(1.0);
+        assert_eq!(session.due_sounds(), [SoundEffect::DieLost]);
+    }
+
+    #[test]
+    fn test_a_table_that_is_never_played_leaves_no_event_log() {
+        let mut settings = Settings::new();
+        settings.event_log = true;
+
+        // Like the table set up behind the main menu
+        let mut session = Session::new(settings, None);
+        session.apply_action(&bid((1, 2))).unwrap();
+        assert!(session.event_log.is_none());
+    }
+
+    #[test]
+    fn test_notices_come_down_after_a_few_seconds() {
+        let mut session = Session::new(Settings::new(), None);
+        session.clock.tick(10.0);
+        session.show_notice("Not now".to_string());
+        session.clock.tick(NOTICE_DURATION / 2.0);
+        session.expire_notice();
+        assert_eq!(session.render_state.notice.as_deref(), Some("Not now"));
+        session.clock.tick(NOTICE_DURATION);
+        session.expire_notice();
+        assert_eq!(session.render_state.notice, None);
+    }
+}
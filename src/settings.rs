@@ -0,0 +1,532 @@
+// Game setup and preferences for Rusty Dice, saved between runs in a settings file
+
+use macroquad::prelude::*;
+
+use crate::ai::AiDifficulty;
+use crate::animation::AnimationConfig;
+use crate::audio::{Channel, Mixer};
+use crate::dice::random_seed;
+use crate::game::{Game, Rules, TimeoutAction, roll_all_dice, DICE_PER_PLAYER, MAX_PLAYERS};
+use crate::i18n::{tr, Language};
+use crate::skins::DiceSkin;
+use crate::theme::{Palette, Theme};
+use crate::tournament::MatchFormat;
+
+pub const SETTINGS_FILE: &str = "settings.cfg";
+
+/// Longest player name, in characters rather than bytes, so accented and CJK names get the same room
+pub const MAX_NAME_LENGTH: usize = 16;
+const MAX_TURN_TIME_LIMIT: u32 = 120;
+const MAX_REVEAL_DURATION: f32 = 10.0;
+const MIN_ANIMATION_SPEED: f32 = 0.25;
+const MAX_ANIMATION_SPEED: f32 = 4.0;
+
+/// How quickly the AI seats move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiPacing {
+    Instant,
+    Fast,
+    Normal,
+    Dramatic,
+}
+
+impl AiPacing {
+    pub const ALL: [AiPacing; 4] = [AiPacing::Instant, AiPacing::Fast, AiPacing::Normal, AiPacing::Dramatic];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AiPacing::Instant => "instant",
+            AiPacing::Fast => "fast",
+            AiPacing::Normal => "normal",
+            AiPacing::Dramatic => "dramatic",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|pacing| pacing.name() == name)
+    }
+
+    /// The closest preset to an `ai_delay_ms` from an older settings file
+    fn from_delay_ms(delay_ms: u64) -> Self {
+        match delay_ms {
+            0 => AiPacing::Instant,
+            1..=500 => AiPacing::Fast,
+            501..=1500 => AiPacing::Normal,
+            _ => AiPacing::Dramatic,
+        }
+    }
+
+    pub fn label(self) -> String {
+        tr(&format!("pacing.{}", self.name()))
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            AiPacing::Instant => AiPacing::Fast,
+            AiPacing::Fast => AiPacing::Normal,
+            AiPacing::Normal => AiPacing::Dramatic,
+            AiPacing::Dramatic => AiPacing::Instant,
+        }
+    }
+
+    /// Seconds an AI seat thinks before moving. `jitter`, from -1 to 1, stretches or
+    /// shortens the pause by up to 40% so the table doesn't tick like a metronome.
+    pub fn thinking_delay(self, jitter: f32) -> f32 {
+        let base = match self {
+            AiPacing::Instant => return 0.0,
+            AiPacing::Fast => 0.35,
+            AiPacing::Normal => 1.0,
+            AiPacing::Dramatic => 2.5,
+        };
+        base * (1.0 + 0.4 * jitter.clamp(-1.0, 1.0))
+    }
+}
+
+/// Who plays a seat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeatKind {
+    Human,
+    Ai(AiDifficulty),
+}
+
+impl SeatKind {
+    pub const ALL: [SeatKind; 4] = [
+        SeatKind::Human,
+        SeatKind::Ai(AiDifficulty::Easy),
+        SeatKind::Ai(AiDifficulty::Normal),
+        SeatKind::Ai(AiDifficulty::Hard),
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SeatKind::Human => "human",
+            SeatKind::Ai(difficulty) => difficulty.name(),
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// The next kind in `ALL`, wrapping around
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&kind| kind == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    // New game setup
+    pub player_count: u8,
+    pub player_names: [String; MAX_PLAYERS],
+    pub seats: [SeatKind; MAX_PLAYERS],
+    pub dice_skins: [DiceSkin; MAX_PLAYERS],
+    pub rules: Rules,
+    pub ai_pacing: AiPacing, // How long AI seats think before each move
+    pub match_format: MatchFormat,
+    pub table_size: u8, // Most players at one table in round-robin and bracket events
+    pub turn_time_limit: u32, // Seconds a human has for each move, 0 for no limit
+    pub timeout_action: TimeoutAction, // Played for a human who runs out of time
+    pub rated: bool, // Whether finished games count towards the ratings leaderboard
+    pub hints: bool, // Start with the odds overlay showing; only allowed in unrated games
+
+    // Preferences
+    pub reveal_duration: f32, // Seconds the matching dice stay lit after a call
+    pub animation_speed: f32, // Multiplier for the flip, removal and roll animations
+    pub theme: Theme,
+    pub colorblind: bool, // Colorblind-safe status colours, with shape and label cues
+    pub mixer: Mixer, // Sound effect volumes and mute
+    pub language: Language,
+    pub event_log: bool, // Write every game's events to the event log folder for analysis
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        let mut seats = [SeatKind::Ai(AiDifficulty::Normal); MAX_PLAYERS];
+        seats[0] = SeatKind::Human;
+
+        Self {
+            player_count: 4,
+            player_names: std::array::from_fn(|i| format!("Player {}", i + 1)),
+            seats,
+            dice_skins: std::array::from_fn(|_| DiceSkin::Theme),
+            rules: Rules::default(),
+            ai_pacing: AiPacing::Normal,
+            match_format: MatchFormat::Single,
+            table_size: 4,
+            turn_time_limit: 0,
+            timeout_action: TimeoutAction::MinimumRaise,
+            rated: true,
+            hints: false,
+            reveal_duration: AnimationConfig::default().highlight_duration,
+            animation_speed: 1.0,
+            theme: Theme::Light,
+            colorblind: false,
+            mixer: Mixer::default(),
+            language: Language::English,
+            event_log: false,
+        }
+    }
+
+    /// Loads settings from the settings file, falling back to the defaults if it is missing
+    pub fn load(path: &str) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(format!("Could not read {}: {}", path, e)),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_config()).map_err(|e| format!("Could not write {}: {}", path, e))
+    }
+
+    /// Parses `setting = value` lines over the defaults. Blank lines and lines
+    /// starting with `#` are ignored.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut settings = Self::new();
+
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("Line {}: expected `setting = value`", line_number + 1));
+            };
+            settings
+                .set(key.trim(), value.trim())
+                .map_err(|e| format!("Line {}: {}", line_number + 1, e))?;
+        }
+
+        Ok(settings)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("invalid value `{}` for `{}`", value, key);
+
+        match key {
+            "player_count" => {
+                self.player_count = value.parse().ok().filter(|count| (2..=MAX_PLAYERS as u8).contains(count)).ok_or_else(invalid)?;
+            }
+            "ones_wild" => self.rules.ones_wild = value.parse().map_err(|_| invalid())?,
+            "starting_dice" => {
+                self.rules.starting_dice = value.parse().ok().filter(|dice| (1..=DICE_PER_PLAYER as u8).contains(dice)).ok_or_else(invalid)?;
+            }
+            "ai_pacing" => self.ai_pacing = AiPacing::from_name(value).ok_or_else(invalid)?,
+            // Older settings files stored a fixed delay instead of a preset
+            "ai_delay_ms" => self.ai_pacing = AiPacing::from_delay_ms(value.parse().map_err(|_| invalid())?),
+            "match_format" => self.match_format = MatchFormat::from_name(value).ok_or_else(invalid)?,
+            "table_size" => {
+                self.table_size = value.parse().ok().filter(|size| (2..=MAX_PLAYERS as u8).contains(size)).ok_or_else(invalid)?;
+            }
+            "turn_time_limit" => {
+                self.turn_time_limit = value.parse().ok().filter(|limit| *limit <= MAX_TURN_TIME_LIMIT).ok_or_else(invalid)?;
+            }
+            "timeout_action" => self.timeout_action = TimeoutAction::from_name(value).ok_or_else(invalid)?,
+            "rated" => self.rated = value.parse().map_err(|_| invalid())?,
+            "hints" => self.hints = value.parse().map_err(|_| invalid())?,
+            "reveal_duration" => {
+                self.reveal_duration = value.parse().ok().filter(|duration| (0.0..=MAX_REVEAL_DURATION).contains(duration)).ok_or_else(invalid)?;
+            }
+            "animation_speed" => {
+                self.animation_speed = value.parse().ok().filter(|speed| (MIN_ANIMATION_SPEED..=MAX_ANIMATION_SPEED).contains(speed)).ok_or_else(invalid)?;
+            }
+            "theme" => self.theme = Theme::from_name(value).ok_or_else(invalid)?,
+            "colorblind" => self.colorblind = value.parse().map_err(|_| invalid())?,
+            "master_volume" => self.mixer.master = parse_volume(value).ok_or_else(invalid)?,
+            "muted" => self.mixer.muted = value.parse().map_err(|_| invalid())?,
+            "language" => self.language = Language::from_name(value).ok_or_else(invalid)?,
+            "event_log" => self.event_log = value.parse().map_err(|_| invalid())?,
+            _ => {
+                // Per-seat settings are numbered from 1: `player_3_name`, `player_3_seat`
+                let seat_setting = key
+                    .strip_prefix("player_")
+                    .and_then(|rest| rest.split_once('_'))
+                    .and_then(|(number, setting)| Some((number.parse::<usize>().ok()?, setting)))
+                    .filter(|(number, _)| (1..=MAX_PLAYERS).contains(number));
+                match seat_setting {
+                    Some((number, "name")) if !value.is_empty() && name_length(value) <= MAX_NAME_LENGTH => {
+                        self.player_names[number - 1] = value.to_string();
+                    }
+                    Some((number, "seat")) => self.seats[number - 1] = SeatKind::from_name(value).ok_or_else(invalid)?,
+                    Some((number, "dice")) => self.dice_skins[number - 1] = DiceSkin::from_name(value).ok_or_else(invalid)?,
+                    Some((_, "name")) => return Err(invalid()),
+                    _ => {
+                        // Channel volumes: `dice_volume`, `table_volume`, `results_volume`
+                        let channel = key
+                            .strip_suffix("_volume")
+                            .and_then(|name| Channel::ALL.into_iter().find(|channel| channel.name() == name))
+                            .ok_or_else(|| format!("unknown setting `{}`", key))?;
+                        self.mixer.set_channel_volume(channel, parse_volume(value).ok_or_else(invalid)?);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The settings file contents for these settings
+    pub fn to_config(&self) -> String {
+        let mut lines = vec![
+            "# Rusty Dice settings".to_string(),
+            format!("player_count = {}", self.player_count),
+        ];
+        for player in 0..MAX_PLAYERS {
+            lines.push(format!("player_{}_name = {}", player + 1, self.player_names[player]));
+            lines.push(format!("player_{}_seat = {}", player + 1, self.seats[player].name()));
+            lines.push(format!("player_{}_dice = {}", player + 1, self.dice_skins[player].name()));
+        }
+        lines.extend([
+            format!("ones_wild = {}", self.rules.ones_wild),
+            format!("starting_dice = {}", self.rules.starting_dice),
+            format!("ai_pacing = {}", self.ai_pacing.name()),
+            format!("match_format = {}", self.match_format.name()),
+            format!("table_size = {}", self.table_size),
+            format!("turn_time_limit = {}", self.turn_time_limit),
+            format!("timeout_action = {}", self.timeout_action.name()),
+            format!("rated = {}", self.rated),
+            format!("hints = {}", self.hints),
+            format!("reveal_duration = {}", self.reveal_duration),
+            format!("animation_speed = {}", self.animation_speed),
+            format!("theme = {}", self.theme.name()),
+            format!("colorblind = {}", self.colorblind),
+            format!("master_volume = {}", self.mixer.master),
+        ]);
+        for channel in Channel::ALL {
+            lines.push(format!("{}_volume = {}", channel.name(), self.mixer.channel_volume(channel)));
+        }
+        lines.push(format!("muted = {}", self.mixer.muted));
+        lines.push(format!("language = {}", self.language.name()));
+        lines.push(format!("event_log = {}", self.event_log));
+        lines.join("\n") + "\n"
+    }
+
+    /// Rated games can't show hints, so turning one on turns the other off
+    pub fn toggle_rated(&mut self) {
+        self.rated = !self.rated;
+        self.hints &= !self.rated;
+    }
+
+    pub fn toggle_hints(&mut self) {
+        self.hints = !self.hints;
+        self.rated &= !self.hints;
+    }
+
+    /// The colours for the chosen theme
+    pub fn palette(&self) -> Palette {
+        self.theme.palette(self.colorblind)
+    }
+
+    /// Whether the hint overlay can be shown in games with this setup
+    pub fn hints_allowed(&self) -> bool {
+        !self.rated
+    }
+
+    pub fn step_player_count(&mut self, step: i8) {
+        self.player_count = self.player_count.saturating_add_signed(step).clamp(2, MAX_PLAYERS as u8);
+    }
+
+    pub fn step_starting_dice(&mut self, step: i8) {
+        self.rules.starting_dice = self.rules.starting_dice.saturating_add_signed(step).clamp(1, DICE_PER_PLAYER as u8);
+    }
+
+    pub fn step_turn_time_limit(&mut self, step: i8) {
+        self.turn_time_limit = self.turn_time_limit.saturating_add_signed(step as i32 * 5).min(MAX_TURN_TIME_LIMIT);
+    }
+
+    pub fn step_table_size(&mut self, step: i8) {
+        self.table_size = self.table_size.saturating_add_signed(step).clamp(2, MAX_PLAYERS as u8);
+    }
+
+    pub fn step_reveal_duration(&mut self, step: f32) {
+        self.reveal_duration = (self.reveal_duration + step * 0.5).clamp(0.0, MAX_REVEAL_DURATION);
+    }
+
+    pub fn step_animation_speed(&mut self, step: f32) {
+        self.animation_speed = (self.animation_speed + step * 0.25).clamp(MIN_ANIMATION_SPEED, MAX_ANIMATION_SPEED);
+    }
+
+    /// Makes the first `human_count` seats human and the rest AI at their chosen difficulty
+    pub fn set_human_count(&mut self, human_count: usize) {
+        for (player, seat) in self.seats.iter_mut().enumerate() {
+            *seat = match (*seat, player < human_count) {
+                (_, true) => SeatKind::Human,
+                (SeatKind::Human, false) => SeatKind::Ai(AiDifficulty::Normal),
+                (kind, false) => kind,
+            };
+        }
+    }
+
+    /// How the AI plays a seat, for seats it plays
+    pub fn ai_difficulty(&self, player: u8) -> AiDifficulty {
+        match self.seats[player as usize] {
+            SeatKind::Ai(difficulty) => difficulty,
+            SeatKind::Human => AiDifficulty::Normal,
+        }
+    }
+
+    pub fn animation_config(&self) -> AnimationConfig {
+        let defaults = AnimationConfig::default();
+        AnimationConfig {
+            flip_duration: defaults.flip_duration / self.animation_speed,
+            highlight_duration: self.reveal_duration,
+            removal_duration: defaults.removal_duration / self.animation_speed,
+            roll_duration: defaults.roll_duration / self.animation_speed,
+        }
+    }
+
+    /// A freshly rolled game for this setup
+    pub fn new_game(&self) -> Game {
+        let mut game = Game::with_players(self.player_count, self.rules);
+        for player in 0..game.player_count as usize {
+            game.player_names[player] = self.player_names[player].clone();
+            game.is_human[player] = self.seats[player] == SeatKind::Human;
+        }
+        game.set_seed(random_seed());
+        roll_all_dice(&mut game);
+        game
+    }
+}
+
+/// How long a name is, as measured against `MAX_NAME_LENGTH`
+pub fn name_length(name: &str) -> usize {
+    name.chars().count()
+}
+
+/// A volume between 0 and 1
+fn parse_volume(value: &str) -> Option<f32> {
+    value.parse().ok().filter(|volume| (0.0..=1.0).contains(volume))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty_file_keeps_defaults() {
+        let settings = Settings::parse("").unwrap();
+        assert_eq!(settings, Settings::new());
+    }
+
+    #[test]
+    fn test_saved_settings_parse_back() {
+        let mut settings = Settings::new();
+        settings.player_count = 6;
+        settings.player_names[2] = "Ana Maria".to_string();
+        // Full length in characters, though far longer in bytes
+        settings.player_names[4] = "José Müller 山田太🎲".to_string();
+        settings.seats[1] = SeatKind::Human;
+        settings.seats[5] = SeatKind::Ai(AiDifficulty::Hard);
+        settings.dice_skins[0] = DiceSkin::Custom("marble".to_string());
+        settings.dice_skins[3] = DiceSkin::Glass(crate::skins::DiceColor::Blue);
+        settings.rules.ones_wild = true;
+        settings.rules.starting_dice = 3;
+        settings.ai_pacing = AiPacing::Dramatic;
+        settings.match_format = MatchFormat::BestOf(5);
+        settings.table_size = 3;
+        settings.turn_time_limit = 30;
+        settings.timeout_action = TimeoutAction::LoseDie;
+        settings.toggle_hints();
+        settings.reveal_duration = 2.5;
+        settings.animation_speed = 1.5;
+        settings.theme = Theme::HighContrast;
+        settings.colorblind = true;
+        settings.mixer.master = 0.35;
+        settings.mixer.set_channel_volume(Channel::Results, 0.5);
+        settings.mixer.muted = true;
+        settings.language = Language::German;
+        settings.event_log = true;
+
+        assert_eq!(Settings::parse(&settings.to_config()).unwrap(), settings);
+    }
+
+    #[test]
+    fn test_parse_out_of_range_value_returns_error() {
+        let result = Settings::parse("player_count = 4\nstarting_dice = 6");
+        assert_eq!(result.unwrap_err(), "Line 2: invalid value `6` for `starting_dice`");
+    }
+
+    #[test]
+    fn test_parse_unknown_setting_returns_error() {
+        assert_eq!(Settings::parse("player_9_seat = human").unwrap_err(), "Line 1: unknown setting `player_9_seat`");
+        assert_eq!(Settings::parse("volume = 11").unwrap_err(), "Line 1: unknown setting `volume`");
+        assert_eq!(Settings::parse("music_volume = 1").unwrap_err(), "Line 1: unknown setting `music_volume`");
+        assert_eq!(Settings::parse("dice_volume = 2").unwrap_err(), "Line 1: invalid value `2` for `dice_volume`");
+    }
+
+    #[test]
+    fn test_steppers_stay_in_range() {
+        let mut settings = Settings::new();
+        settings.step_player_count(10);
+        assert_eq!(settings.player_count, 8);
+        settings.step_player_count(-10);
+        assert_eq!(settings.player_count, 2);
+        settings.step_starting_dice(-10);
+        assert_eq!(settings.rules.starting_dice, 1);
+        settings.step_animation_speed(-10.0);
+        assert_eq!(settings.animation_speed, MIN_ANIMATION_SPEED);
+        settings.step_turn_time_limit(-1);
+        assert_eq!(settings.turn_time_limit, 0);
+        settings.step_turn_time_limit(100);
+        assert_eq!(settings.turn_time_limit, MAX_TURN_TIME_LIMIT);
+    }
+
+    #[test]
+    fn test_old_ai_delay_maps_to_nearest_pacing() {
+        assert_eq!(Settings::parse("ai_delay_ms = 0").unwrap().ai_pacing, AiPacing::Instant);
+        assert_eq!(Settings::parse("ai_delay_ms = 250").unwrap().ai_pacing, AiPacing::Fast);
+        assert_eq!(Settings::parse("ai_delay_ms = 1000").unwrap().ai_pacing, AiPacing::Normal);
+        assert_eq!(Settings::parse("ai_delay_ms = 5000").unwrap().ai_pacing, AiPacing::Dramatic);
+    }
+
+    #[test]
+    fn test_thinking_delay_varies_around_the_pace() {
+        assert_eq!(AiPacing::Instant.thinking_delay(1.0), 0.0);
+        assert_eq!(AiPacing::Normal.thinking_delay(0.0), 1.0);
+        assert!((AiPacing::Normal.thinking_delay(-1.0) - 0.6).abs() < 1e-6);
+        assert!((AiPacing::Normal.thinking_delay(5.0) - 1.4).abs() < 1e-6);
+        for pacing in [AiPacing::Fast, AiPacing::Normal] {
+            assert!(pacing.thinking_delay(1.0) < pacing.next().thinking_delay(-1.0));
+        }
+    }
+
+    #[test]
+    fn test_new_game_uses_setup() {
+        let mut settings = Settings::new();
+        settings.player_count = 3;
+        settings.player_names[1] = "Bea".to_string();
+        settings.rules.starting_dice = 2;
+        settings.set_human_count(2);
+
+        let game = settings.new_game();
+        assert_eq!(game.player_count, 3);
+        assert_eq!(game.player_names[1], "Bea");
+        assert_eq!(game.is_human[..3], [true, true, false]);
+        assert_eq!(game.total_dice(), 6);
+    }
+
+    #[test]
+    fn test_animation_speed_scales_animations_but_not_reveal() {
+        let mut settings = Settings::new();
+        settings.animation_speed = 2.0;
+        settings.reveal_duration = 3.0;
+        let config = settings.animation_config();
+        assert_eq!(config.roll_duration, AnimationConfig::default().roll_duration / 2.0);
+        assert_eq!(config.highlight_duration, 3.0);
+    }
+
+    #[test]
+    fn test_hints_and_rated_games_exclude_each_other() {
+        let mut settings = Settings::new();
+        assert!(settings.rated && !settings.hints_allowed());
+
+        settings.toggle_hints();
+        assert!(settings.hints && !settings.rated && settings.hints_allowed());
+
+        settings.toggle_rated();
+        assert!(settings.rated && !settings.hints);
+    }
+}
@@ -0,0 +1,244 @@
+// Dice skins each seat can pick, including custom faces loaded from PNG files
+
+use std::path::Path;
+
+use macroquad::prelude::*;
+
+use crate::dice::{DiceFaces, DiceStyle};
+use crate::i18n::{tr, tr_args};
+use crate::theme::Palette;
+
+/// Custom skins live in `dice_skins/<name>/`, with one picture per face named `1.png` to `6.png`
+pub const DICE_SKINS_DIR: &str = "dice_skins";
+
+/// A colour for solid and glass dice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiceColor {
+    Red,
+    Blue,
+    Green,
+    Purple,
+    Ivory,
+    Black,
+}
+
+impl DiceColor {
+    pub const ALL: [DiceColor; 6] = [DiceColor::Red, DiceColor::Blue, DiceColor::Green, DiceColor::Purple, DiceColor::Ivory, DiceColor::Black];
+
+    fn name(self) -> &'static str {
+        match self {
+            DiceColor::Red => "red",
+            DiceColor::Blue => "blue",
+            DiceColor::Green => "green",
+            DiceColor::Purple => "purple",
+            DiceColor::Ivory => "ivory",
+            DiceColor::Black => "black",
+        }
+    }
+
+    fn label(self) -> String {
+        tr(&format!("color.{}", self.name()))
+    }
+
+    fn color(self) -> Color {
+        match self {
+            DiceColor::Red => Color::new(0.78, 0.12, 0.14, 1.0),
+            DiceColor::Blue => Color::new(0.12, 0.3, 0.72, 1.0),
+            DiceColor::Green => Color::new(0.1, 0.5, 0.25, 1.0),
+            DiceColor::Purple => Color::new(0.45, 0.2, 0.6, 1.0),
+            DiceColor::Ivory => Color::new(0.96, 0.94, 0.86, 1.0),
+            DiceColor::Black => Color::new(0.1, 0.1, 0.1, 1.0),
+        }
+    }
+
+    /// Pips that stand out on this colour
+    fn pips(self) -> Color {
+        match self {
+            DiceColor::Ivory => BLACK,
+            _ => WHITE,
+        }
+    }
+}
+
+/// How a seat's dice look
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiceSkin {
+    Theme, // The theme's own dice
+    Solid(DiceColor),
+    Glass(DiceColor), // Translucent, so the table shows through
+    Numerals,         // The theme's dice with numbers instead of pips
+    Custom(String),   // A folder of PNG faces in `DICE_SKINS_DIR`
+}
+
+impl DiceSkin {
+    /// The built-in skins, in the order the setup screen cycles through them
+    pub fn built_in() -> Vec<DiceSkin> {
+        let mut skins = vec![DiceSkin::Theme];
+        skins.extend(DiceColor::ALL.map(DiceSkin::Solid));
+        skins.extend([DiceColor::Red, DiceColor::Blue, DiceColor::Green].map(DiceSkin::Glass));
+        skins.push(DiceSkin::Numerals);
+        skins
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            DiceSkin::Theme => "theme".to_string(),
+            DiceSkin::Solid(color) => color.name().to_string(),
+            DiceSkin::Glass(color) => format!("glass_{}", color.name()),
+            DiceSkin::Numerals => "numerals".to_string(),
+            DiceSkin::Custom(name) => format!("custom:{}", name),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(custom) = name.strip_prefix("custom:") {
+            return is_skin_folder_name(custom).then(|| DiceSkin::Custom(custom.to_string()));
+        }
+        Self::built_in().into_iter().find(|skin| skin.name() == name)
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            DiceSkin::Theme => tr("skin.theme"),
+            DiceSkin::Solid(color) => capitalize(&color.label()),
+            DiceSkin::Glass(color) => tr_args("skin.glass", &[("color", &color.label())]),
+            DiceSkin::Numerals => tr("skin.numerals"),
+            DiceSkin::Custom(name) => name.clone(),
+        }
+    }
+
+    /// The next skin after this one: the built-in skins, then the custom ones found on disk
+    pub fn next(&self, custom_names: &[String]) -> Self {
+        let mut skins = Self::built_in();
+        skins.extend(custom_names.iter().cloned().map(DiceSkin::Custom));
+        let index = skins.iter().position(|skin| skin == self);
+        match index {
+            Some(index) => skins[(index + 1) % skins.len()].clone(),
+            None => DiceSkin::Theme,
+        }
+    }
+
+    /// How dice in this skin are drawn. Custom skins whose pictures couldn't be loaded fall
+    /// back to the theme's dice.
+    pub fn style(&self, palette: &Palette, custom_faces: Option<&[Texture2D; 6]>) -> DiceStyle {
+        let theme = DiceStyle {
+            body: palette.die,
+            marks: palette.pips,
+            faces: DiceFaces::Pips,
+        };
+        match self {
+            DiceSkin::Theme => theme,
+            DiceSkin::Solid(color) => DiceStyle {
+                body: color.color(),
+                marks: color.pips(),
+                ..theme
+            },
+            DiceSkin::Glass(color) => DiceStyle {
+                body: Color { a: 0.45, ..color.color() },
+                marks: palette.text,
+                ..theme
+            },
+            DiceSkin::Numerals => DiceStyle {
+                faces: DiceFaces::Numerals,
+                ..theme
+            },
+            DiceSkin::Custom(_) => match custom_faces {
+                Some(faces) => DiceStyle {
+                    faces: DiceFaces::Images(Box::new(faces.clone())),
+                    ..theme
+                },
+                None => theme,
+            },
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+}
+
+/// Custom skin names are folder names, so they can't reach outside `DICE_SKINS_DIR`
+fn is_skin_folder_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+/// The custom skins in `dir` that have all six faces, sorted by name
+pub fn custom_skin_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| (1..=6).all(|face| entry.path().join(format!("{}.png", face)).is_file()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| is_skin_folder_name(name))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Loads the six faces of a custom skin from `dir`
+pub fn load_custom_faces(dir: &Path, name: &str) -> Result<[Texture2D; 6], String> {
+    let mut faces = Vec::new();
+    for face in 1..=6 {
+        let path = dir.join(name).join(format!("{}.png", face));
+        let bytes = std::fs::read(&path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let image = Image::from_file_with_format(&bytes, Some(ImageFormat::Png)).map_err(|e| format!("Could not load {}: {}", path.display(), e))?;
+        faces.push(Texture2D::from_image(&image));
+    }
+    faces.try_into().map_err(|_| "A dice skin needs six faces".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::theme::Theme;
+
+    #[test]
+    fn test_skin_names_round_trip() {
+        for skin in DiceSkin::built_in() {
+            assert_eq!(DiceSkin::from_name(&skin.name()), Some(skin));
+        }
+        assert_eq!(DiceSkin::from_name("custom:wood"), Some(DiceSkin::Custom("wood".to_string())));
+        assert_eq!(DiceSkin::from_name("custom:../wood"), None);
+        assert_eq!(DiceSkin::from_name("custom:"), None);
+        assert_eq!(DiceSkin::from_name("plaid"), None);
+    }
+
+    #[test]
+    fn test_next_cycles_through_custom_skins_and_wraps() {
+        let customs = vec!["marble".to_string(), "wood".to_string()];
+        assert_eq!(DiceSkin::Numerals.next(&customs), DiceSkin::Custom("marble".to_string()));
+        assert_eq!(DiceSkin::Custom("wood".to_string()).next(&customs), DiceSkin::Theme);
+        assert_eq!(DiceSkin::Numerals.next(&[]), DiceSkin::Theme);
+        // A custom skin that has been deleted starts the cycle again
+        assert_eq!(DiceSkin::Custom("gone".to_string()).next(&customs), DiceSkin::Theme);
+    }
+
+    #[test]
+    fn test_styles_follow_the_skin() {
+        let palette = Theme::Dark.palette(false);
+        assert_eq!(DiceSkin::Theme.style(&palette, None).body, palette.die);
+        assert_eq!(DiceSkin::Solid(DiceColor::Black).style(&palette, None).marks, WHITE);
+        assert!(DiceSkin::Glass(DiceColor::Red).style(&palette, None).body.a < 1.0);
+        assert_eq!(DiceSkin::Numerals.style(&palette, None).faces, DiceFaces::Numerals);
+        assert_eq!(DiceSkin::Custom("missing".to_string()).style(&palette, None).faces, DiceFaces::Pips);
+    }
+
+    #[test]
+    fn test_custom_skins_need_all_six_faces() {
+        let dir = std::env::temp_dir().join(format!("rusty-dice-skins-{}", std::process::id()));
+        for (name, faces) in [("wood", 6), ("half", 3)] {
+            std::fs::create_dir_all(dir.join(name)).unwrap();
+            for face in 1..=faces {
+                std::fs::write(dir.join(name).join(format!("{}.png", face)), b"").unwrap();
+            }
+        }
+
+        assert_eq!(custom_skin_names(&dir), ["wood"]);
+        assert!(custom_skin_names(&dir.join("missing")).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
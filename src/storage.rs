@@ -0,0 +1,25 @@
+// Where Rusty Dice keeps files that outlive a single game
+
+use std::path::PathBuf;
+
+const APP_DIR: &str = "rusty-dice";
+
+/// The per-user data directory: `$XDG_DATA_HOME/rusty-dice`, `~/.local/share/rusty-dice`,
+/// or `%APPDATA%\rusty-dice` on Windows. Falls back to the working directory.
+pub fn data_dir() -> PathBuf {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")));
+    match base {
+        Some(base) => base.join(APP_DIR),
+        None => PathBuf::from("."),
+    }
+}
+
+/// A file in the data directory, creating the directory if needed
+pub fn data_file(name: &str) -> Result<PathBuf, String> {
+    let dir = data_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    Ok(dir.join(name))
+}
@@ -0,0 +1,226 @@
+// Colour themes for the table and menus, and the colorblind-safe adjustments to them
+
+use macroquad::prelude::*;
+
+use crate::game::MAX_PLAYERS;
+use crate::i18n::tr;
+
+/// A colour for each seat, marking its name at the table and its bids in the history. These
+/// are the Okabe-Ito colours, which stay apart with colour blindness, so every theme shares them.
+pub const SEAT_COLORS: [Color; MAX_PLAYERS] = [
+    Color::new(0.9, 0.62, 0.0, 1.0),
+    Color::new(0.34, 0.71, 0.91, 1.0),
+    Color::new(0.0, 0.62, 0.45, 1.0),
+    Color::new(0.94, 0.89, 0.26, 1.0),
+    Color::new(0.0, 0.45, 0.7, 1.0),
+    Color::new(0.84, 0.37, 0.0, 1.0),
+    Color::new(0.8, 0.47, 0.65, 1.0),
+    Color::new(0.6, 0.6, 0.6, 1.0),
+];
+
+/// Colours the table is drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Light,
+    Dark,
+    HighContrast,
+    Felt,
+}
+
+/// Every colour the game draws with, for one theme
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub background: Color,
+    pub table: Color,
+    pub text: Color,
+    pub muted_text: Color,
+    pub panel: Color,  // Behind the bid picker and other controls
+    pub die: Color,
+    pub pips: Color,
+    pub matching_die: Color, // Dice that count towards a called bet
+    pub button: Color,
+    pub button_hover: Color,
+    pub button_text: Color,
+    pub confirm: Color, // The bet button when the bet is legal
+    pub disabled: Color,
+    pub focus: Color,
+    pub positive: Color, // Dice revealed, plenty of time left
+    pub warning: Color,  // Dice hidden
+    pub danger: Color,   // Time nearly up, tutorial mistakes
+    pub shape_cues: bool, // Mark states with outlines, strike-throughs and labels as well as colour
+}
+
+impl Theme {
+    pub const ALL: [Theme; 4] = [Theme::Light, Theme::Dark, Theme::HighContrast, Theme::Felt];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high_contrast",
+            Theme::Felt => "felt",
+        }
+    }
+
+    /// Settings files from before the dark themes called the light theme "classic"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "classic" => Some(Theme::Light),
+            _ => Self::ALL.into_iter().find(|theme| theme.name() == name),
+        }
+    }
+
+    pub fn label(self) -> String {
+        tr(&format!("theme.{}", self.name()))
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::HighContrast,
+            Theme::HighContrast => Theme::Felt,
+            Theme::Felt => Theme::Light,
+        }
+    }
+
+    fn is_dark(self) -> bool {
+        matches!(self, Theme::Dark | Theme::HighContrast)
+    }
+
+    /// The theme's colours. The colorblind-safe palette swaps the green, orange and red
+    /// status colours for blue, orange and vermillion, which stay apart with red-green
+    /// colour blindness, and turns on the shape cues.
+    pub fn palette(self, colorblind: bool) -> Palette {
+        let light = Palette {
+            background: WHITE,
+            table: Color::new(0.93, 0.93, 0.93, 1.0),
+            text: BLACK,
+            muted_text: DARKGRAY,
+            panel: LIGHTGRAY,
+            die: GRAY,
+            pips: BLACK,
+            matching_die: GOLD,
+            button: BLUE,
+            button_hover: DARKBLUE,
+            button_text: WHITE,
+            confirm: GREEN,
+            disabled: DARKGRAY,
+            focus: BLACK,
+            positive: GREEN,
+            warning: ORANGE,
+            danger: RED,
+            shape_cues: false,
+        };
+
+        let palette = match self {
+            Theme::Light => light,
+            Theme::Felt => Palette {
+                background: Color::new(0.87, 0.91, 0.86, 1.0),
+                table: Color::new(0.47, 0.71, 0.52, 1.0),
+                die: Color::new(0.96, 0.94, 0.88, 1.0),
+                ..light
+            },
+            Theme::Dark => Palette {
+                background: Color::new(0.11, 0.12, 0.14, 1.0),
+                table: Color::new(0.2, 0.23, 0.27, 1.0),
+                text: Color::new(0.92, 0.92, 0.92, 1.0),
+                muted_text: Color::new(0.68, 0.69, 0.72, 1.0),
+                panel: Color::new(0.26, 0.28, 0.32, 1.0),
+                die: Color::new(0.78, 0.78, 0.8, 1.0),
+                pips: Color::new(0.08, 0.08, 0.1, 1.0),
+                button: Color::new(0.16, 0.4, 0.78, 1.0),
+                button_hover: Color::new(0.1, 0.3, 0.62, 1.0),
+                confirm: Color::new(0.1, 0.52, 0.26, 1.0),
+                disabled: Color::new(0.36, 0.37, 0.4, 1.0),
+                focus: Color::new(0.92, 0.92, 0.92, 1.0),
+                positive: Color::new(0.4, 0.85, 0.45, 1.0),
+                danger: Color::new(1.0, 0.4, 0.4, 1.0),
+                ..light
+            },
+            Theme::HighContrast => Palette {
+                background: BLACK,
+                table: Color::new(0.12, 0.12, 0.12, 1.0),
+                text: WHITE,
+                muted_text: Color::new(0.85, 0.85, 0.85, 1.0),
+                panel: Color::new(0.2, 0.2, 0.2, 1.0),
+                die: WHITE,
+                pips: BLACK,
+                matching_die: YELLOW,
+                button: Color::new(0.0, 0.3, 0.85, 1.0),
+                button_hover: Color::new(0.0, 0.2, 0.6, 1.0),
+                confirm: Color::new(0.0, 0.45, 0.0, 1.0),
+                disabled: Color::new(0.3, 0.3, 0.3, 1.0),
+                focus: YELLOW,
+                positive: Color::new(0.3, 1.0, 0.3, 1.0),
+                warning: Color::new(1.0, 0.7, 0.0, 1.0),
+                danger: Color::new(1.0, 0.35, 0.35, 1.0),
+                ..light
+            },
+        };
+
+        if !colorblind {
+            return palette;
+        }
+        // Okabe-Ito colours, with a lighter blue where it has to show up on a dark background
+        let blue = if self.is_dark() { Color::new(0.55, 0.85, 1.0, 1.0) } else { Color::new(0.0, 0.45, 0.7, 1.0) };
+        Palette {
+            confirm: Color::new(0.0, 0.45, 0.7, 1.0),
+            positive: blue,
+            warning: Color::new(0.9, 0.62, 0.0, 1.0),
+            danger: Color::new(0.84, 0.37, 0.0, 1.0),
+            shape_cues: true,
+            ..palette
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // WCAG relative luminance and contrast ratio
+    fn luminance(color: Color) -> f32 {
+        let channel = |c: f32| if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) };
+        0.2126 * channel(color.r) + 0.7152 * channel(color.g) + 0.0722 * channel(color.b)
+    }
+
+    fn contrast(a: Color, b: Color) -> f32 {
+        let (a, b) = (luminance(a), luminance(b));
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    #[test]
+    fn test_theme_names_round_trip() {
+        for theme in Theme::ALL {
+            assert_eq!(Theme::from_name(theme.name()), Some(theme));
+        }
+        assert_eq!(Theme::from_name("classic"), Some(Theme::Light));
+        assert_eq!(Theme::from_name("neon"), None);
+    }
+
+    #[test]
+    fn test_text_is_readable_in_every_theme() {
+        for theme in Theme::ALL {
+            for colorblind in [false, true] {
+                let palette = theme.palette(colorblind);
+                assert!(contrast(palette.text, palette.background) >= 7.0, "{:?} text", theme);
+                assert!(contrast(palette.text, palette.panel) >= 4.5, "{:?} panel text", theme);
+                assert!(contrast(palette.pips, palette.die) >= 4.5, "{:?} pips", theme);
+                assert!(contrast(palette.pips, palette.matching_die) >= 4.5, "{:?} matching pips", theme);
+                // Button labels are large text, which needs less contrast
+                assert!(contrast(palette.button_text, palette.button) >= 3.0, "{:?} buttons", theme);
+            }
+        }
+    }
+
+    #[test]
+    fn test_colorblind_palette_adds_shape_cues() {
+        for theme in Theme::ALL {
+            let palette = theme.palette(true);
+            assert!(palette.shape_cues);
+            assert!(!theme.palette(false).shape_cues);
+            // Revealed and hidden differ in brightness as well as hue
+            assert!(contrast(palette.positive, palette.warning) > 1.2, "{:?}", theme);
+        }
+    }
+}
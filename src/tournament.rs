@@ -0,0 +1,523 @@
+// Multi-game matches and tournaments: best-of-N at one table, or round-robin and
+// bracket events over several tables
+
+use std::path::Path;
+
+use crate::game::Game;
+use crate::i18n::{tr, tr_args};
+use crate::settings::{SeatKind, Settings};
+use crate::skins::DiceSkin;
+
+/// How a series of games is organised
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchFormat {
+    Single,
+    BestOf(u8),  // Everyone at one table until someone has won most of the games
+    RoundRobin,  // Rounds of tables until every entrant has played every other
+    Bracket,     // The top half of each table goes through until one final table is left
+}
+
+impl MatchFormat {
+    pub const ALL: [MatchFormat; 5] = [
+        MatchFormat::Single,
+        MatchFormat::BestOf(3),
+        MatchFormat::BestOf(5),
+        MatchFormat::RoundRobin,
+        MatchFormat::Bracket,
+    ];
+
+    pub fn name(self) -> String {
+        match self {
+            MatchFormat::Single => "single".to_string(),
+            MatchFormat::BestOf(games) => format!("best_of_{}", games),
+            MatchFormat::RoundRobin => "round_robin".to_string(),
+            MatchFormat::Bracket => "bracket".to_string(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "single" => Some(MatchFormat::Single),
+            "round_robin" => Some(MatchFormat::RoundRobin),
+            "bracket" => Some(MatchFormat::Bracket),
+            _ => {
+                let games: u8 = name.strip_prefix("best_of_")?.parse().ok()?;
+                (games % 2 == 1).then_some(MatchFormat::BestOf(games))
+            }
+        }
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            MatchFormat::Single => tr("format.single"),
+            MatchFormat::BestOf(games) => tr_args("format.best_of", &[("games", &games)]),
+            MatchFormat::RoundRobin => tr("format.round_robin"),
+            MatchFormat::Bracket => tr("format.bracket"),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&format| format == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Whether entrants are split over several tables of `table_size`
+    pub fn uses_tables(self) -> bool {
+        matches!(self, MatchFormat::RoundRobin | MatchFormat::Bracket)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entrant {
+    pub name: String,
+    pub seat: SeatKind,
+    pub dice_skin: DiceSkin,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Standing {
+    pub points: u32, // One for every player finishing below, per game
+    pub wins: u32,
+    pub games: u32,
+    pub best_round: u32, // Last round played in, for brackets
+}
+
+/// One finished game: which round it was in and its entrants from first to last
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableResult {
+    pub round: u32,
+    pub finishing_order: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tournament {
+    pub format: MatchFormat,
+    pub entrants: Vec<Entrant>,
+    pub table_size: usize,
+    pub results: Vec<TableResult>,
+    pub round: u32,
+    pending: Vec<Vec<usize>>, // Tables still to play this round, next first
+    champion: Option<usize>,
+}
+
+impl Tournament {
+    pub fn new(format: MatchFormat, entrants: Vec<Entrant>, table_size: usize) -> Self {
+        let table_size = if format.uses_tables() {
+            table_size.clamp(2, entrants.len().max(2))
+        } else {
+            entrants.len()
+        };
+        let everyone: Vec<usize> = (0..entrants.len()).collect();
+        let mut tournament = Self {
+            format,
+            entrants,
+            table_size,
+            results: Vec::new(),
+            round: 1,
+            pending: Vec::new(),
+            champion: None,
+        };
+        tournament.pending = tournament.schedule_round(&everyone);
+        tournament
+    }
+
+    /// The match for the setup chosen on the setup screen
+    pub fn from_settings(settings: &Settings) -> Self {
+        let entrants = (0..settings.player_count as usize)
+            .map(|player| Entrant {
+                name: settings.player_names[player].clone(),
+                seat: settings.seats[player],
+                dice_skin: settings.dice_skins[player].clone(),
+            })
+            .collect();
+        Self::new(settings.match_format, entrants, settings.table_size as usize)
+    }
+
+    /// Entrants at the next table to play, in seat order
+    pub fn current_table(&self) -> Option<&[usize]> {
+        self.pending.first().map(Vec::as_slice)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn champion(&self) -> Option<usize> {
+        self.champion
+    }
+
+    /// Settings for a game at the current table: the base setup's rules with the table's entrants seated
+    pub fn table_settings(&self, base: &Settings) -> Option<Settings> {
+        let table = self.current_table()?;
+        let mut settings = base.clone();
+        settings.player_count = table.len() as u8;
+        for (seat, &entrant) in table.iter().enumerate() {
+            settings.player_names[seat] = self.entrants[entrant].name.clone();
+            settings.seats[seat] = self.entrants[entrant].seat;
+            settings.dice_skins[seat] = self.entrants[entrant].dice_skin.clone();
+        }
+        Some(settings)
+    }
+
+    /// Records the finished game at the current table, then moves on to the next table or round
+    pub fn record_game(&mut self, game: &Game) {
+        let Some(table) = self.current_table() else {
+            return;
+        };
+        let finishing_order: Vec<usize> = game
+            .finishing_order()
+            .into_iter()
+            .filter_map(|seat| table.get(seat as usize).copied())
+            .collect();
+        self.record_result(finishing_order);
+    }
+
+    fn record_result(&mut self, finishing_order: Vec<usize>) {
+        if self.pending.is_empty() {
+            return;
+        }
+        self.pending.remove(0);
+        self.results.push(TableResult {
+            round: self.round,
+            finishing_order,
+        });
+        if !self.pending.is_empty() {
+            return;
+        }
+
+        // The round is over: decide whether there is another
+        let everyone: Vec<usize> = (0..self.entrants.len()).collect();
+        match self.format {
+            MatchFormat::Single => self.champion = self.results[0].finishing_order.first().copied(),
+            MatchFormat::BestOf(games) => {
+                let standings = self.standings();
+                let (leader, standing) = &standings[0];
+                if standing.wins > games as u32 / 2 || self.results.len() >= games as usize {
+                    self.champion = Some(*leader);
+                } else {
+                    self.round += 1;
+                    self.pending = vec![everyone];
+                }
+            }
+            MatchFormat::RoundRobin => {
+                let next_round = self.schedule_round(&everyone);
+                if next_round.is_empty() {
+                    self.champion = self.standings().first().map(|(entrant, _)| *entrant);
+                } else {
+                    self.round += 1;
+                    self.pending = next_round;
+                }
+            }
+            MatchFormat::Bracket => {
+                let round_results: Vec<&TableResult> = self.results.iter().filter(|result| result.round == self.round).collect();
+                if round_results.len() == 1 {
+                    // Everyone left was at one table, so that was the final
+                    self.champion = round_results[0].finishing_order.first().copied();
+                } else {
+                    let advancing: Vec<usize> = round_results
+                        .iter()
+                        .flat_map(|result| {
+                            let advance = (result.finishing_order.len() / 2).max(1);
+                            result.finishing_order[..advance].to_vec()
+                        })
+                        .collect();
+                    self.round += 1;
+                    self.pending = self.schedule_round(&advancing);
+                }
+            }
+        }
+    }
+
+    /// Tables for a round among `players`, or none when the tournament is over
+    fn schedule_round(&self, players: &[usize]) -> Vec<Vec<usize>> {
+        match self.format {
+            MatchFormat::Single | MatchFormat::BestOf(_) => vec![players.to_vec()],
+            MatchFormat::Bracket => split_into_tables(players, self.table_size),
+            MatchFormat::RoundRobin => self.next_round_robin_tables(),
+        }
+    }
+
+    /// Seats everyone so each table brings together as many entrants who haven't met yet as
+    /// possible. Returns no tables once every pair has shared a table.
+    fn next_round_robin_tables(&self) -> Vec<Vec<usize>> {
+        let entrant_count = self.entrants.len();
+        let mut met = vec![vec![false; entrant_count]; entrant_count];
+        for result in &self.results {
+            for &a in &result.finishing_order {
+                for &b in &result.finishing_order {
+                    met[a][b] = true;
+                }
+            }
+        }
+        let unmet = |a: usize, met: &Vec<Vec<bool>>| (0..entrant_count).filter(|&b| b != a && !met[a][b]).count();
+        if (0..entrant_count).all(|a| unmet(a, &met) == 0) {
+            return Vec::new();
+        }
+
+        let mut unseated: Vec<usize> = (0..entrant_count).collect();
+        let mut tables = Vec::new();
+        for table_size in table_sizes(entrant_count, self.table_size) {
+            let mut table: Vec<usize> = Vec::new();
+            while table.len() < table_size {
+                // Start a table with whoever has the most entrants left to meet, then add
+                // whoever has met the fewest of those already seated
+                let (index, _) = unseated
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|&(index, &entrant)| {
+                        let score = if table.is_empty() {
+                            unmet(entrant, &met)
+                        } else {
+                            table.iter().filter(|&&seated| !met[entrant][seated]).count()
+                        };
+                        (score, std::cmp::Reverse(index))
+                    })
+                    .expect("a table is never larger than the entrants left to seat");
+                table.push(unseated.remove(index));
+            }
+            tables.push(table);
+        }
+        tables
+    }
+
+    /// Every entrant with their totals, best first
+    pub fn standings(&self) -> Vec<(usize, Standing)> {
+        let mut standings: Vec<(usize, Standing)> = (0..self.entrants.len()).map(|entrant| (entrant, Standing::default())).collect();
+        for result in &self.results {
+            let field_size = result.finishing_order.len();
+            for (place, &entrant) in result.finishing_order.iter().enumerate() {
+                let standing = &mut standings[entrant].1;
+                standing.points += (field_size - 1 - place) as u32;
+                standing.games += 1;
+                standing.best_round = standing.best_round.max(result.round);
+                if place == 0 {
+                    standing.wins += 1;
+                }
+            }
+        }
+
+        let champion = self.champion;
+        standings.sort_by_key(|(entrant, standing)| {
+            let is_champion = Some(*entrant) == champion;
+            let key = match self.format {
+                MatchFormat::BestOf(_) => (standing.wins, standing.points, 0),
+                MatchFormat::Bracket => (standing.best_round, standing.points, standing.wins),
+                MatchFormat::Single | MatchFormat::RoundRobin => (standing.points, standing.wins, 0),
+            };
+            std::cmp::Reverse((is_champion, key))
+        });
+        standings
+    }
+
+    pub fn save_results(&self, path: &Path) -> Result<(), String> {
+        std::fs::write(path, self.to_results()).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    /// A plain text report of the standings and every game, for saving to a results file.
+    /// It is always in English, with the format's settings name, so it reads the same
+    /// whatever language the game is shown in.
+    pub fn to_results(&self) -> String {
+        let mut lines = vec![
+            "Rusty Dice tournament results".to_string(),
+            format!("Format: {}", self.format.name()),
+        ];
+        if self.format.uses_tables() {
+            lines.push(format!("Tables of up to {}", self.table_size));
+        }
+
+        lines.push(String::new());
+        lines.push("Standings".to_string());
+        for (place, (entrant, standing)) in self.standings().iter().enumerate() {
+            lines.push(format!(
+                "{}. {} ({}): {} points, {} wins, {} games",
+                place + 1,
+                self.entrants[*entrant].name,
+                self.entrants[*entrant].seat.name(),
+                standing.points,
+                standing.wins,
+                standing.games,
+            ));
+        }
+
+        lines.push(String::new());
+        lines.push("Games".to_string());
+        let mut table_number = 0;
+        for (i, result) in self.results.iter().enumerate() {
+            let new_round = i == 0 || self.results[i - 1].round != result.round;
+            table_number = if new_round { 1 } else { table_number + 1 };
+            let order: Vec<&str> = result.finishing_order.iter().map(|&entrant| self.entrants[entrant].name.as_str()).collect();
+            lines.push(format!("Round {}, table {}: {}", result.round, table_number, order.join(", ")));
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Sizes for splitting `count` players into as few tables of at most `table_size` as
+/// possible, as evenly as possible. Nobody sits alone: a player left over from heads-up
+/// tables joins one of them instead.
+fn table_sizes(count: usize, table_size: usize) -> Vec<usize> {
+    let table_count = count.div_ceil(table_size.max(1)).min(count / 2).max(1);
+    (0..table_count)
+        .map(|table| count / table_count + usize::from(table < count % table_count))
+        .collect()
+}
+
+fn split_into_tables(players: &[usize], table_size: usize) -> Vec<Vec<usize>> {
+    let mut remaining = players;
+    table_sizes(players.len(), table_size)
+        .into_iter()
+        .map(|size| {
+            let (table, rest) = remaining.split_at(size);
+            remaining = rest;
+            table.to_vec()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::ai::AiDifficulty;
+    use crate::skins::DiceColor;
+
+    fn entrants(count: usize) -> Vec<Entrant> {
+        (0..count)
+            .map(|i| Entrant {
+                name: format!("Player {}", i + 1),
+                seat: SeatKind::Ai(AiDifficulty::Normal),
+                dice_skin: DiceSkin::Theme,
+            })
+            .collect()
+    }
+
+    /// Plays every table with the entrants finishing in seat order
+    fn play_out(tournament: &mut Tournament) {
+        while let Some(table) = tournament.current_table() {
+            assert!(table.len() >= 2, "round {} has a table of {:?}", tournament.round, table);
+            let order = table.to_vec();
+            tournament.record_result(order);
+        }
+    }
+
+    #[test]
+    fn test_best_of_stops_at_a_majority() {
+        let mut tournament = Tournament::new(MatchFormat::BestOf(5), entrants(3), 3);
+        tournament.record_result(vec![0, 1, 2]);
+        tournament.record_result(vec![1, 0, 2]);
+        assert!(!tournament.is_finished());
+        tournament.record_result(vec![0, 2, 1]);
+        tournament.record_result(vec![0, 1, 2]);
+
+        assert!(tournament.is_finished());
+        assert_eq!(tournament.results.len(), 4);
+        assert_eq!(tournament.champion(), Some(0));
+        let standings = tournament.standings();
+        assert_eq!(standings[0].1.wins, 3);
+        assert_eq!(standings[0].1.points, 7);
+    }
+
+    #[test]
+    fn test_round_robin_seats_everyone_together() {
+        let mut tournament = Tournament::new(MatchFormat::RoundRobin, entrants(6), 3);
+        assert_eq!(tournament.current_table().unwrap().len(), 3);
+        play_out(&mut tournament);
+
+        for a in 0..6 {
+            for b in 0..6 {
+                let shared = tournament
+                    .results
+                    .iter()
+                    .any(|result| result.finishing_order.contains(&a) && result.finishing_order.contains(&b));
+                assert!(shared, "Entrants {} and {} never shared a table", a, b);
+            }
+        }
+        assert!(tournament.champion().is_some());
+    }
+
+    #[test]
+    fn test_round_robin_heads_up_plays_every_pair_once() {
+        let mut tournament = Tournament::new(MatchFormat::RoundRobin, entrants(4), 2);
+        play_out(&mut tournament);
+        assert_eq!(tournament.results.len(), 6);
+    }
+
+    #[test]
+    fn test_bracket_advances_top_half_to_a_final() {
+        let mut tournament = Tournament::new(MatchFormat::Bracket, entrants(8), 4);
+        assert_eq!(tournament.current_table(), Some(&[0, 1, 2, 3][..]));
+        tournament.record_result(vec![3, 2, 1, 0]);
+        tournament.record_result(vec![4, 5, 6, 7]);
+
+        // The top two from each table meet at the final table
+        assert_eq!(tournament.round, 2);
+        assert_eq!(tournament.current_table(), Some(&[3, 2, 4, 5][..]));
+        tournament.record_result(vec![5, 3, 2, 4]);
+
+        assert!(tournament.is_finished());
+        assert_eq!(tournament.champion(), Some(5));
+        let standings = tournament.standings();
+        assert_eq!(standings[0].0, 5);
+        assert_eq!(standings[1].0, 3);
+        assert!(standings[4..].iter().all(|(_, standing)| standing.best_round == 1));
+    }
+
+    #[test]
+    fn test_table_sizes_are_even() {
+        assert_eq!(table_sizes(8, 4), vec![4, 4]);
+        assert_eq!(table_sizes(7, 3), vec![3, 2, 2]);
+        assert_eq!(table_sizes(5, 8), vec![5]);
+        assert_eq!(table_sizes(3, 2), vec![3]);
+        assert_eq!(table_sizes(7, 2), vec![3, 2, 2]);
+    }
+
+    #[test]
+    fn test_odd_fields_heads_up_never_seat_anyone_alone() {
+        for format in [MatchFormat::Bracket, MatchFormat::RoundRobin] {
+            for count in [3, 5, 7] {
+                let mut tournament = Tournament::new(format, entrants(count), 2);
+                play_out(&mut tournament);
+
+                assert!(tournament.champion().is_some(), "{:?} with {} entrants", format, count);
+                assert!(tournament.results.iter().all(|result| result.finishing_order.len() >= 2));
+                // Every table win came from beating someone
+                let wins: u32 = tournament.standings().iter().map(|(_, standing)| standing.wins).sum();
+                assert_eq!(wins as usize, tournament.results.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_results_report_lists_standings_and_games() {
+        let mut tournament = Tournament::new(MatchFormat::Single, entrants(2), 2);
+        tournament.record_result(vec![1, 0]);
+        let results = tournament.to_results();
+        assert!(results.contains("Format: single"));
+        assert!(results.contains("1. Player 2 (normal): 1 points, 1 wins, 1 games"));
+        assert!(results.contains("Round 1, table 1: Player 2, Player 1"));
+    }
+
+    #[test]
+    fn test_entrants_bring_their_dice_to_each_table() {
+        let mut settings = Settings::new();
+        settings.match_format = MatchFormat::RoundRobin;
+        settings.table_size = 2;
+        for player in 0..4 {
+            settings.dice_skins[player] = DiceSkin::Solid(DiceColor::ALL[player]);
+        }
+
+        let tournament = Tournament::from_settings(&settings);
+        let table_settings = tournament.table_settings(&settings).unwrap();
+        for (seat, &entrant) in tournament.current_table().unwrap().iter().enumerate() {
+            assert_eq!(table_settings.dice_skins[seat], settings.dice_skins[entrant]);
+        }
+    }
+
+    #[test]
+    fn test_format_names_round_trip() {
+        for format in MatchFormat::ALL {
+            assert_eq!(MatchFormat::from_name(&format.name()), Some(format));
+        }
+        assert_eq!(MatchFormat::from_name("best_of_4"), None);
+    }
+}
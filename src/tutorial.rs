@@ -0,0 +1,413 @@
+// Scripted lessons that walk a new player through the game. The lessons themselves live in
+// lessons/, one file per language, so new ones can be added without touching the code.
+
+use std::path::Path;
+
+use crate::ai::AiDifficulty;
+use crate::dice::DiceRng;
+use crate::game::{roll_all_dice, Action, Game, Phase, PlayerAction, Rules};
+use crate::i18n::{tr, tr_args, Language};
+use crate::notation::{parse_hands, parse_move, parse_player_tag, parse_rules};
+use crate::settings::{SeatKind, Settings};
+
+/// Lessons are read from `lessons/<language>.txt`, falling back to the copies built into the game
+pub const LESSONS_DIR: &str = "lessons";
+
+const ENGLISH: &str = include_str!("../lessons/en.txt");
+const SPANISH: &str = include_str!("../lessons/es.txt");
+const GERMAN: &str = include_str!("../lessons/de.txt");
+
+fn bundled_lessons(language: Language) -> &'static str {
+    match language {
+        Language::English => ENGLISH,
+        Language::Spanish => SPANISH,
+        Language::German => GERMAN,
+    }
+}
+
+// The learner always plays from the first seat
+const LEARNER: u8 = 0;
+
+/// Part of the table a callout points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Table,
+    Hand, // The learner's own dice
+    History,
+    Controls,
+    Picker,
+    Call,
+    Panel, // Round results beside the table
+}
+
+impl Target {
+    fn from_name(name: &str) -> Option<Self> {
+        let target = match name {
+            "table" => Target::Table,
+            "hand" => Target::Hand,
+            "history" => Target::History,
+            "controls" => Target::Controls,
+            "picker" => Target::Picker,
+            "call" => Target::Call,
+            "panel" => Target::Panel,
+            _ => return None,
+        };
+        Some(target)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    Deal(Vec<u8>), // Every die for the next round, in the order the engine rolls them
+    Note { target: Target, text: String },
+    Move { player: u8, action: PlayerAction }, // Played for a computer seat, or expected from the learner
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lesson {
+    pub title: String,
+    pub rules: Rules,
+    pub player_names: Vec<String>,
+    pub steps: Vec<Step>,
+}
+
+impl Lesson {
+    /// The lesson's table, dealt from its first `Hands:` line
+    fn new_game(&self) -> Game {
+        let mut game = Game::with_players(self.player_names.len() as u8, self.rules);
+        for (player, name) in self.player_names.iter().enumerate() {
+            game.player_names[player] = name.clone();
+            game.is_human[player] = player == LEARNER as usize;
+        }
+        if let Some(Step::Deal(rolls)) = self.steps.first() {
+            game.rng = DiceRng::scripted(rolls);
+        }
+        roll_all_dice(&mut game);
+        game
+    }
+}
+
+/// Reads lessons from the tutorial file format described at the top of lessons/en.txt
+pub fn parse_lessons(text: &str) -> Result<Vec<Lesson>, String> {
+    let mut lessons: Vec<Lesson> = Vec::new();
+
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: String| format!("Line {}: {}", line_number + 1, message);
+
+        if let Some(title) = line.strip_prefix("Lesson:") {
+            lessons.push(Lesson {
+                title: title.trim().to_string(),
+                rules: Rules::default(),
+                player_names: Vec::new(),
+                steps: Vec::new(),
+            });
+            continue;
+        }
+        let lesson = lessons.last_mut().ok_or(error("expected `Lesson:` first".to_string()))?;
+
+        if let Some(rules) = line.strip_prefix("Rules:") {
+            lesson.rules = parse_rules(rules.trim()).ok_or(error(format!("invalid rules `{}`", rules.trim())))?;
+        } else if let Some(hands) = line.strip_prefix("Hands:") {
+            let mut hands = parse_hands(hands.trim()).map_err(error)?;
+            hands.sort_by_key(|(player, _)| *player);
+            lesson.steps.push(Step::Deal(hands.into_iter().flat_map(|(_, dice)| dice).collect()));
+        } else if let Some(note) = line.strip_prefix("Note ") {
+            let (target, text) = note.split_once(':').ok_or(error("expected `Note target: text`".to_string()))?;
+            let target = Target::from_name(target.trim()).ok_or(error(format!("unknown callout target `{}`", target.trim())))?;
+            lesson.steps.push(Step::Note {
+                target,
+                text: text.trim().to_string(),
+            });
+        } else if let Some((tag, name)) = line.split_once(" = ") {
+            let player = parse_player_tag(tag).ok_or(error(format!("unknown player `{}`", tag)))?;
+            if player as usize != lesson.player_names.len() {
+                return Err(error(format!("expected P{} next", lesson.player_names.len() + 1)));
+            }
+            lesson.player_names.push(name.trim().to_string());
+        } else if let Some((tag, move_text)) = line.split_once(": ") {
+            let player = parse_player_tag(tag).ok_or(error(format!("unknown player `{}`", tag)))?;
+            let action = parse_move(move_text.trim()).map_err(error)?;
+            lesson.steps.push(Step::Move { player, action });
+        } else {
+            return Err(error(format!("unrecognised line `{}`", line)));
+        }
+    }
+
+    match lessons.iter().find(|lesson| lesson.player_names.len() < 2 || !matches!(lesson.steps.first(), Some(Step::Deal(_)))) {
+        Some(lesson) => Err(format!("Lesson `{}` needs two players and a `Hands:` line before anything else", lesson.title)),
+        None => Ok(lessons),
+    }
+}
+
+/// How a move is described to the learner
+pub fn describe_move(action: &PlayerAction) -> String {
+    match action.bet {
+        Some((dice_count, face_value)) if action.action == Action::Bet => tr_args("tutorial.move_bid", &[("count", &dice_count), ("face", &face_value)]),
+        _ => tr("tutorial.move_call"),
+    }
+}
+
+/// Progress through the lessons
+pub struct Tutorial {
+    pub lessons: Vec<Lesson>,
+    pub lesson: usize,
+    pub step: usize,
+    pub message: Option<String>, // Feedback on the learner's last move
+}
+
+impl Tutorial {
+    /// Starts the lessons in a language, read from `dir` or built in if there is no file for it
+    pub fn load(language: Language, dir: &Path) -> Result<Self, String> {
+        let path = dir.join(format!("{}.txt", language.name()));
+        let lessons = if path.is_file() {
+            let text = std::fs::read_to_string(&path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+            parse_lessons(&text).map_err(|e| format!("{}: {}", path.display(), e))?
+        } else {
+            parse_lessons(bundled_lessons(language))?
+        };
+        Self::with_lessons(lessons)
+    }
+
+    fn with_lessons(lessons: Vec<Lesson>) -> Result<Self, String> {
+        if lessons.is_empty() {
+            return Err("The tutorial has no lessons".to_string());
+        }
+        Ok(Self {
+            lessons,
+            lesson: 0,
+            step: 1, // The first deal happens when the game is set up
+            message: None,
+        })
+    }
+
+    pub fn current_lesson(&self) -> &Lesson {
+        &self.lessons[self.lesson]
+    }
+
+    pub fn current_step(&self) -> Option<&Step> {
+        self.current_lesson().steps.get(self.step)
+    }
+
+    pub fn is_last_lesson(&self) -> bool {
+        self.lesson + 1 == self.lessons.len()
+    }
+
+    /// The setup for the current lesson's game: the learner against scripted seats, unrated
+    pub fn settings(&self, base: &Settings) -> Settings {
+        let lesson = self.current_lesson();
+        let mut settings = base.clone();
+        settings.player_count = lesson.player_names.len() as u8;
+        settings.rules = lesson.rules;
+        settings.rated = false;
+        settings.hints = false;
+        settings.turn_time_limit = 0;
+        for (player, name) in lesson.player_names.iter().enumerate() {
+            settings.player_names[player] = name.clone();
+            settings.seats[player] = if player == LEARNER as usize { SeatKind::Human } else { SeatKind::Ai(AiDifficulty::Normal) };
+        }
+        settings
+    }
+
+    /// The current lesson's table with its first hands dealt
+    pub fn new_game(&self) -> Game {
+        self.current_lesson().new_game()
+    }
+
+    pub fn next_lesson(&mut self) {
+        self.lesson = (self.lesson + 1).min(self.lessons.len() - 1);
+        self.step = 1;
+        self.message = None;
+    }
+
+    /// Moves past a callout once it has been read
+    pub fn dismiss_note(&mut self) {
+        if matches!(self.current_step(), Some(Step::Note { .. })) {
+            self.step += 1;
+        }
+    }
+
+    /// Whether a called round has to stay on the table, because the lesson still has
+    /// something to say about it before the next deal
+    pub fn holds_table(&self, game: &Game) -> bool {
+        game.phase == Phase::Revealing && !matches!(self.current_step(), Some(Step::Deal(_)) | None)
+    }
+
+    /// Loads the next round's dice just before they are rolled
+    pub fn prepare_deal(&mut self, game: &mut Game) {
+        if let Some(Step::Deal(rolls)) = self.current_step() {
+            if game.phase == Phase::Dealing {
+                game.rng = DiceRng::scripted(rolls);
+                self.step += 1;
+            }
+        }
+    }
+
+    /// The move to play for a computer seat, when the script has one due
+    pub fn scripted_move(&self, game: &Game) -> Option<PlayerAction> {
+        match self.current_step() {
+            Some(Step::Move { player, action }) if *player != LEARNER && *player == game.current_player && game.phase == Phase::Bidding => {
+                Some(action.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// What the learner is expected to do next, if it is their move
+    pub fn expected_move(&self) -> Option<&PlayerAction> {
+        match self.current_step() {
+            Some(Step::Move { player, action }) if *player == LEARNER => Some(action),
+            _ => None,
+        }
+    }
+
+    /// Records that the scripted move for a computer seat has been played
+    pub fn played_scripted_move(&mut self) {
+        self.step += 1;
+    }
+
+    /// Accepts the learner's move only if it is the one the lesson is waiting for
+    pub fn try_move(&mut self, action: &PlayerAction) -> bool {
+        match self.expected_move() {
+            Some(expected) if expected == action => {
+                self.step += 1;
+                self.message = None;
+                true
+            }
+            Some(expected) => {
+                self.message = Some(tr_args("tutorial.wrong_move", &[("move", &describe_move(expected))]));
+                false
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::{advance_phase, take_action};
+
+    /// Plays a lesson the way the game does, failing on any step the engine rejects
+    fn play_through(tutorial: &mut Tutorial) -> Game {
+        let mut game = tutorial.new_game();
+        while let Some(step) = tutorial.current_step().cloned() {
+            match step {
+                Step::Deal(_) => {
+                    assert_ne!(game.phase, Phase::Bidding, "{}: deal before the round was called", tutorial.current_lesson().title);
+                    while game.phase != Phase::Bidding {
+                        tutorial.prepare_deal(&mut game);
+                        game = advance_phase(&game).unwrap();
+                    }
+                }
+                Step::Note { .. } => tutorial.dismiss_note(),
+                Step::Move { player, action } => {
+                    assert_eq!(player, game.current_player, "{}: step {} is out of turn", tutorial.current_lesson().title, tutorial.step);
+                    if player == LEARNER {
+                        assert!(tutorial.try_move(&action));
+                    } else {
+                        assert_eq!(tutorial.scripted_move(&game), Some(action.clone()));
+                        tutorial.played_scripted_move();
+                    }
+                    game = take_action(&game, &action).unwrap();
+                }
+            }
+        }
+        game
+    }
+
+    #[test]
+    fn test_every_lesson_plays_through() {
+        let mut tutorial = Tutorial::with_lessons(parse_lessons(ENGLISH).unwrap()).unwrap();
+        for lesson in 0..tutorial.lessons.len() {
+            tutorial.lesson = lesson;
+            tutorial.step = 1;
+            play_through(&mut tutorial);
+        }
+    }
+
+    #[test]
+    fn test_lessons_deal_their_scripted_hands() {
+        let tutorial = Tutorial::with_lessons(parse_lessons(ENGLISH).unwrap()).unwrap();
+        let game = tutorial.new_game();
+        assert_eq!(game.player_dice[0][..3], [3, 5, 5]);
+        assert_eq!(game.player_dice[1][..3], [2, 6, 4]);
+        assert_eq!(game.player_names[1], "Ana");
+    }
+
+    #[test]
+    fn test_elimination_lesson_ends_with_the_learner_winning() {
+        let mut tutorial = Tutorial::with_lessons(parse_lessons(ENGLISH).unwrap()).unwrap();
+        tutorial.lesson = tutorial.lessons.iter().position(|lesson| lesson.title == "Elimination").unwrap();
+        let game = play_through(&mut tutorial);
+        assert_eq!(game.winner, Some(LEARNER));
+        assert_eq!(game.finishing_order(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_only_the_expected_move_is_accepted() {
+        let mut tutorial = Tutorial::with_lessons(parse_lessons(ENGLISH).unwrap()).unwrap();
+        while tutorial.expected_move().is_none() {
+            tutorial.dismiss_note();
+        }
+        let wrong = PlayerAction {
+            action: Action::Bet,
+            bet: Some((1, 5)),
+        };
+        assert!(!tutorial.try_move(&wrong));
+        assert_eq!(tutorial.message.as_deref(), Some("Not this time - bid 2 x 5 instead"));
+
+        let right = tutorial.expected_move().cloned().unwrap();
+        assert!(tutorial.try_move(&right));
+        assert_eq!(tutorial.message, None);
+    }
+
+    #[test]
+    fn test_translated_lessons_follow_the_english_script() {
+        let english = parse_lessons(ENGLISH).unwrap();
+        for language in Language::ALL {
+            let lessons = parse_lessons(bundled_lessons(language)).unwrap();
+            assert_eq!(lessons.len(), english.len(), "{:?}", language);
+            for (lesson, original) in lessons.iter().zip(&english) {
+                assert_eq!(lesson.rules, original.rules, "{:?} {}", language, lesson.title);
+                assert_eq!(lesson.player_names.len(), original.player_names.len(), "{:?} {}", language, lesson.title);
+                // Only the callouts' words differ
+                let script = |steps: &[Step]| -> Vec<Step> {
+                    steps
+                        .iter()
+                        .map(|step| match step {
+                            Step::Note { target, .. } => Step::Note { target: *target, text: String::new() },
+                            step => step.clone(),
+                        })
+                        .collect()
+                };
+                assert_eq!(script(&lesson.steps), script(&original.steps), "{:?} {}", language, lesson.title);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lessons_load_from_files_with_the_bundled_ones_as_fallback() {
+        let dir = std::env::temp_dir().join(format!("rusty-dice-lessons-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("de.txt"), "Lesson: Probe\nRules: 3 dice\nP1 = Du\nP2 = Ana\nHands: P1 123, P2 456\n").unwrap();
+
+        assert_eq!(Tutorial::load(Language::German, &dir).unwrap().current_lesson().title, "Probe");
+        assert_eq!(Tutorial::load(Language::Spanish, &dir).unwrap().current_lesson().player_names[0], "Tú");
+        std::fs::write(dir.join("de.txt"), "Rules: 3 dice\n").unwrap();
+        assert!(Tutorial::load(Language::German, &dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_lessons_reports_bad_lines() {
+        let error = parse_lessons("Lesson: Test\nRules: 3 dice\nP1 = You\nP2 = Ana\nHands: P1 1, P2 2\nNote shelf: Hi\n").unwrap_err();
+        assert_eq!(error, "Line 6: unknown callout target `shelf`");
+        assert!(parse_lessons("Rules: 3 dice\n").is_err());
+        assert!(parse_lessons("Lesson: Test\nP1 = You\nP2 = Ana\nP1: 1x2\n").is_err());
+    }
+}